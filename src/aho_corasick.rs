@@ -0,0 +1,120 @@
+// Aho–Corasick automaton: finds every occurrence of many patterns in one pass over a text,
+// by walking a trie of the patterns and falling back along failure links on a mismatch.
+
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// Trie edges, sorted by character.
+    next: Vec<(char, usize)>,
+    /// Longest proper suffix of this node's string that is also a trie node.
+    fail: usize,
+    /// Patterns ending at this node, including those reached through failure links.
+    outputs: Vec<usize>,
+}
+
+impl Node {
+    fn child(&self, ch: char) -> Option<usize> {
+        self.next.binary_search_by_key(&ch, |&(edge, _)| edge).ok().map(|i| self.next[i].1)
+    }
+}
+
+/// An occurrence of a pattern: `pattern` is its index in the list given to [`AhoCorasick::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Match {
+    pub start: usize,
+    pub len: usize,
+    pub pattern: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    nodes: Vec<Node>,
+    lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    /// An automaton for `patterns`; empty patterns never match.
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> AhoCorasick {
+        let mut nodes = vec![Node::default()];
+        let mut lens = Vec::with_capacity(patterns.len());
+        for (pattern_index, pattern) in patterns.iter().enumerate() {
+            let pattern = pattern.as_ref();
+            lens.push(pattern.chars().count());
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut node = 0;
+            for ch in pattern.chars() {
+                node = match nodes[node].child(ch) {
+                    Some(child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        let i = nodes[node].next.partition_point(|&(edge, _)| edge < ch);
+                        nodes[node].next.insert(i, (ch, child));
+                        child
+                    }
+                };
+            }
+            nodes[node].outputs.push(pattern_index);
+        }
+
+        // Breadth first, so the failure link of every shorter string is known already
+        let mut queue: VecDeque<usize> = nodes[0].next.iter().map(|&(_, child)| child).collect();
+        while let Some(node) = queue.pop_front() {
+            for (ch, child) in nodes[node].next.clone() {
+                let mut fail = nodes[node].fail;
+                let child_fail = loop {
+                    match nodes[fail].child(ch) {
+                        Some(next) if next != child => break next,
+                        _ if fail == 0 => break 0,
+                        _ => fail = nodes[fail].fail,
+                    }
+                };
+                nodes[child].fail = child_fail;
+                let inherited = nodes[child_fail].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+        AhoCorasick { nodes, lens }
+    }
+
+    /// Number of characters in pattern `pattern`.
+    pub fn pattern_len(&self, pattern: usize) -> usize {
+        self.lens[pattern]
+    }
+
+    /// Length of the longest pattern.
+    pub fn max_len(&self) -> usize {
+        self.lens.iter().copied().max().unwrap_or(0)
+    }
+
+    fn step(&self, mut node: usize, ch: char) -> usize {
+        loop {
+            if let Some(next) = self.nodes[node].child(ch) {
+                return next;
+            }
+            if node == 0 {
+                return 0;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Every occurrence of every pattern in `text`, overlapping ones included, ordered by
+    /// where they end.
+    pub fn find_all(&self, text: impl IntoIterator<Item = char>) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut node = 0;
+        for (i, ch) in text.into_iter().enumerate() {
+            node = self.step(node, ch);
+            for &pattern in &self.nodes[node].outputs {
+                let len = self.lens[pattern];
+                matches.push(Match { start: i + 1 - len, len, pattern });
+            }
+        }
+        matches
+    }
+}
@@ -0,0 +1,231 @@
+// Dense bit sets over grid cells, as a faster replacement for `HashSet<(usize, usize)>`
+// and `Vec<Vec<bool>>` visited sets. Cells are addressed as `(row, col)`.
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// Fixed-size set of bits stored in `u64` words, shared by both grid types.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Bits {
+    words: Vec<u64>,
+}
+
+impl Bits {
+    fn new(len: usize) -> Bits {
+        Bits { words: vec![0; len.div_ceil(WORD_BITS)] }
+    }
+
+    fn get(&self, i: usize) -> bool {
+        self.words[i / WORD_BITS] >> (i % WORD_BITS) & 1 == 1
+    }
+
+    /// Sets bit `i`, returning `true` if it was not set before.
+    fn insert(&mut self, i: usize) -> bool {
+        let word = &mut self.words[i / WORD_BITS];
+        let mask = 1 << (i % WORD_BITS);
+        let was_set = *word & mask != 0;
+        *word |= mask;
+        !was_set
+    }
+
+    /// Clears bit `i`, returning `true` if it was set before.
+    fn remove(&mut self, i: usize) -> bool {
+        let word = &mut self.words[i / WORD_BITS];
+        let mask = 1 << (i % WORD_BITS);
+        let was_set = *word & mask != 0;
+        *word &= !mask;
+        was_set
+    }
+
+    fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn union_with(&mut self, other: &Bits) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    fn intersect_with(&mut self, other: &Bits) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    /// Indices of the set bits in increasing order.
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(w, &word)| {
+            let mut rest = word;
+            std::iter::from_fn(move || {
+                if rest == 0 {
+                    return None;
+                }
+                let bit = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                Some(w * WORD_BITS + bit)
+            })
+        })
+    }
+}
+
+/// One bit per cell of a `rows x cols` grid.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    rows: usize,
+    cols: usize,
+    bits: Bits,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> BitGrid {
+        BitGrid { rows, cols, bits: Bits::new(rows * cols) }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn index(&self, row: usize, col: usize) -> usize {
+        assert!(row < self.rows && col < self.cols, "({}, {}) is outside of a {}x{} grid", row, col, self.rows, self.cols);
+        row * self.cols + col
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.bits.get(self.index(row, col))
+    }
+
+    /// Sets the cell, returning `true` if it was not set before (like `HashSet::insert`).
+    pub fn insert(&mut self, row: usize, col: usize) -> bool {
+        let i = self.index(row, col);
+        self.bits.insert(i)
+    }
+
+    /// Clears the cell, returning `true` if it was set before.
+    pub fn remove(&mut self, row: usize, col: usize) -> bool {
+        let i = self.index(row, col);
+        self.bits.remove(i)
+    }
+
+    /// Clears every cell, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    /// Number of set cells.
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    /// Adds every cell of `other`, which must have the same dimensions.
+    pub fn union_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.rows, self.cols), (other.rows, other.cols));
+        self.bits.union_with(&other.bits);
+    }
+
+    /// Keeps only the cells also set in `other`, which must have the same dimensions.
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        assert_eq!((self.rows, self.cols), (other.rows, other.cols));
+        self.bits.intersect_with(&other.bits);
+    }
+
+    /// Set cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.bits.iter().map(|i| (i / self.cols, i % self.cols))
+    }
+}
+
+/// One bit per cell and layer, e.g. a visited set of `(position, direction)` states.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid3 {
+    rows: usize,
+    cols: usize,
+    layers: usize,
+    bits: Bits,
+}
+
+impl BitGrid3 {
+    pub fn new(rows: usize, cols: usize, layers: usize) -> BitGrid3 {
+        BitGrid3 { rows, cols, layers, bits: Bits::new(rows * cols * layers) }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn layers(&self) -> usize {
+        self.layers
+    }
+
+    // Layers are the innermost dimension so all states of one cell share a word
+    fn index(&self, row: usize, col: usize, layer: usize) -> usize {
+        assert!(
+            row < self.rows && col < self.cols && layer < self.layers,
+            "({}, {}, {}) is outside of a {}x{}x{} grid",
+            row, col, layer, self.rows, self.cols, self.layers
+        );
+        (row * self.cols + col) * self.layers + layer
+    }
+
+    pub fn contains(&self, row: usize, col: usize, layer: usize) -> bool {
+        self.bits.get(self.index(row, col, layer))
+    }
+
+    /// Sets the state, returning `true` if it was not set before.
+    pub fn insert(&mut self, row: usize, col: usize, layer: usize) -> bool {
+        let i = self.index(row, col, layer);
+        self.bits.insert(i)
+    }
+
+    /// Clears the state, returning `true` if it was set before.
+    pub fn remove(&mut self, row: usize, col: usize, layer: usize) -> bool {
+        let i = self.index(row, col, layer);
+        self.bits.remove(i)
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.clear();
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn union_with(&mut self, other: &BitGrid3) {
+        assert_eq!((self.rows, self.cols, self.layers), (other.rows, other.cols, other.layers));
+        self.bits.union_with(&other.bits);
+    }
+
+    pub fn intersect_with(&mut self, other: &BitGrid3) {
+        assert_eq!((self.rows, self.cols, self.layers), (other.rows, other.cols, other.layers));
+        self.bits.intersect_with(&other.bits);
+    }
+
+    /// Set states as `(row, col, layer)`, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        self.bits.iter().map(|i| {
+            let (cell, layer) = (i / self.layers, i % self.layers);
+            (cell / self.cols, cell % self.cols, layer)
+        })
+    }
+
+    /// Cells that are set in any layer.
+    pub fn flatten(&self) -> BitGrid {
+        let mut grid = BitGrid::new(self.rows, self.cols);
+        for (row, col, _) in self.iter() {
+            grid.insert(row, col);
+        }
+        grid
+    }
+}
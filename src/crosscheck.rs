@@ -0,0 +1,164 @@
+// Differential testing: runs every implementation of a part on the same inputs, the
+// real puzzle input and randomly generated ones, and reports the first input they
+// disagree on, shrunk to the smallest input that still shows the disagreement.
+
+use crate::days::{self, Day, Part};
+use crate::random::Rng;
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Seed of the first random input; every further input uses the next seed.
+    pub seed: u64,
+    /// Number of random inputs.
+    pub runs: usize,
+    /// Size of the largest random input, passed to the day's generator.
+    pub max_size: usize,
+    /// Also check the real puzzle input, if there is one.
+    pub real_input: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { seed: 1, runs: 100, max_size: 50, real_input: true }
+    }
+}
+
+/// Answers of every implementation, or the panic message of the ones that failed.
+pub type Results = Vec<(&'static str, Result<String, String>)>;
+
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    /// Where the input came from, e.g. `seed 17, size 40`.
+    pub source: String,
+    /// Smallest input found that still shows the disagreement.
+    pub input: String,
+    pub results: Results,
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub part: u32,
+    pub implementations: Vec<&'static str>,
+    /// Number of inputs checked, up to and including the one with the disagreement.
+    pub inputs: usize,
+    pub disagreement: Option<Disagreement>,
+}
+
+fn run_all(implementations: &[(&'static str, Part)], input: &str) -> Results {
+    implementations
+        .iter()
+        .map(|&(name, solve)| (name, days::run_caught(solve, input).result().map(str::to_string).map_err(str::to_string)))
+        .collect()
+}
+
+fn disagree(results: &Results) -> bool {
+    results.windows(2).any(|pair| pair[0].1 != pair[1].1)
+}
+
+/// Whether `results` show the same kind of disagreement as `original`: shrinking must not
+/// turn a wrong answer into an input that some implementation merely cannot parse.
+fn same_failure(original: &Results, results: &Results) -> bool {
+    let failed = |results: &Results| results.iter().map(|(_, result)| result.is_err()).collect::<Vec<_>>();
+    disagree(results) && failed(original) == failed(results)
+}
+
+/// Removes chunks of `items`, halving the chunk size down to single items, as long as
+/// `still_fails` holds for what is left.
+fn shrink_items<T: Clone>(items: &mut Vec<T>, mut still_fails: impl FnMut(&[T]) -> bool) -> bool {
+    let mut shrunk = false;
+    let mut chunk = items.len().div_ceil(2);
+    while chunk >= 1 && !items.is_empty() {
+        let mut start = 0;
+        while start < items.len() {
+            let end = (start + chunk).min(items.len());
+            let candidate: Vec<T> = items[..start].iter().chain(&items[end..]).cloned().collect();
+            if still_fails(&candidate) {
+                *items = candidate;
+                shrunk = true;
+            } else {
+                start = end;
+            }
+        }
+        chunk /= 2;
+    }
+    shrunk
+}
+
+fn join_lines(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+/// Splits a line into the tokens removed while shrinking: words, or characters for lines without spaces.
+fn tokens(line: &str) -> (Vec<String>, &'static str) {
+    match line.contains(char::is_whitespace) {
+        true => (line.split_whitespace().map(str::to_string).collect(), " "),
+        false => (line.chars().map(String::from).collect(), ""),
+    }
+}
+
+/// Shrinks `input` by dropping lines, then words (or characters) within lines, until
+/// nothing more can be dropped without losing the disagreement.
+pub fn shrink(implementations: &[(&'static str, Part)], input: &str, original: &Results) -> String {
+    let mut still_fails = |lines: &[String]| same_failure(original, &run_all(implementations, &join_lines(lines)));
+    let mut lines: Vec<String> = input.lines().map(str::to_string).collect();
+    loop {
+        let mut shrunk = shrink_items(&mut lines, &mut still_fails);
+        for i in 0..lines.len() {
+            let (mut line_tokens, separator) = tokens(&lines[i]);
+            let with_line = |line_tokens: &[String], lines: &[String]| {
+                let mut candidate = lines.to_vec();
+                candidate[i] = line_tokens.join(separator);
+                candidate
+            };
+            let current = lines.clone();
+            if shrink_items(&mut line_tokens, |line_tokens| still_fails(&with_line(line_tokens, &current))) {
+                lines = with_line(&line_tokens, &current);
+                shrunk = true;
+            }
+        }
+        if !shrunk {
+            return join_lines(&lines);
+        }
+    }
+}
+
+/// Checks all implementations of `part` against each other; `None` if the part has fewer than two.
+pub fn crosscheck(day: &Day, part: u32, options: &Options) -> Option<Report> {
+    let implementations = day.implementations(part);
+    if implementations.len() < 2 {
+        return None;
+    }
+    let mut report = Report {
+        part,
+        implementations: implementations.iter().map(|&(name, _)| name).collect(),
+        inputs: 0,
+        disagreement: None,
+    };
+
+    let mut inputs: Vec<(String, String)> = Vec::new();
+    if options.real_input {
+        if let Ok(input) = day.input() {
+            inputs.push((day.input_path().display().to_string(), input));
+        }
+    }
+    if let Some(generate) = day.generate {
+        // Sizes grow from 1 to `max_size`, so small disagreements show up first
+        for run in 0..options.runs {
+            let seed = options.seed.wrapping_add(run as u64);
+            let size = 1 + run * options.max_size.saturating_sub(1) / options.runs.saturating_sub(1).max(1);
+            inputs.push((format!("seed {}, size {}", seed, size), generate(&mut Rng::new(seed), size)));
+        }
+    }
+
+    for (source, input) in inputs {
+        report.inputs += 1;
+        let results = run_all(&implementations, &input);
+        if disagree(&results) {
+            let input = shrink(&implementations, &input, &results);
+            let results = run_all(&implementations, &input);
+            report.disagreement = Some(Disagreement { source, input, results });
+            break;
+        }
+    }
+    Some(report)
+}
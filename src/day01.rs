@@ -1,5 +1,15 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::external_sort::{ExternalCounter, ExternalSorter};
+use crate::parse::{self, ParseError};
+use crate::random::Rng;
+
 // --- Day 1: Historian Hysteria ---
-fn part_one(location_distances: Vec<(i32, i32)>) {
+pub fn part_one(input: &str) -> u128 {
+    let mut lists = read_lists(input);
     // find the total distance between the left list and the right list,
     // add up the distances between all of the pairs you found
 
@@ -12,19 +22,12 @@ fn part_one(location_distances: Vec<(i32, i32)>) {
     // 3   3
 
     // 2 + 1 + 0 + 1 + 2 + 5 = 11
-    let mut dist_left: Vec<i32> = location_distances.iter().map(|&(a, _)| a).collect();
-    let mut dist_right: Vec<i32> = location_distances.iter().map(|&(_, b)| b).collect();
-
-    dist_left.sort();
-    dist_right.sort();
-
-    // zip dist_left and dist_right and make abs subtraction of them
-    let dist_diff: Vec<i32> = dist_left.iter().zip(dist_right.iter()).map(|(a, b)| (a - b).abs()).collect();
-    println!("Part 1: {}", dist_diff.iter().sum::<i32>());
+    lists.total_distance()
 }
 
 
-fn part_two(location_distances: Vec<(i32, i32)>) {
+pub fn part_two(input: &str) -> u128 {
+    let lists = read_lists(input);
     // Calculate a total similarity score by adding up each number in the left list
     // after multiplying it by the number of times that number appears in the right list.
 
@@ -36,37 +39,322 @@ fn part_two(location_distances: Vec<(i32, i32)>) {
     // 3   9
     // 3   3
     // result 9 + 4 + 0 + 0 + 9 + 9 = 31
+    lists.similarity_score()
+}
+
+/// Both answers for the lists read from `reader`, e.g. a file or stdin.
+pub fn solve_stream(reader: &mut dyn BufRead) -> io::Result<(u128, u128)> {
+    let mut lists = LocationLists::read(reader)?;
+    let similarity_score = lists.similarity_score();
+    Ok((lists.total_distance(), similarity_score))
+}
+
+/// Answers of [`solve_external`], with what it took to compute them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalAnswers {
+    pub distance: u128,
+    pub similarity: u128,
+    /// Sorted runs and count runs written to temporary files.
+    pub runs: usize,
+    /// Most bytes held at once by the sort buffers, the frequency map and the merges, as
+    /// estimated from their capacities; allocator overhead and the reader are not included.
+    pub estimated_peak_memory: usize,
+}
+
+/// Both answers for lists of any length, holding about `memory` bytes: the columns are
+/// sorted in runs spilled to `temp_dir`, and merged back to pair up the sorted IDs.
+/// The similarity score comes from right-column counts kept in a map of bounded size,
+/// which are merged alongside the sorted left column.
+pub fn solve_external(reader: &mut dyn BufRead, memory: usize, temp_dir: &Path) -> io::Result<ExternalAnswers> {
+    let mut left: ExternalSorter<u64> = ExternalSorter::with_memory(temp_dir, memory / 4);
+    let mut right: ExternalSorter<u64> = ExternalSorter::with_memory(temp_dir, memory / 4);
+    let mut right_counts = ExternalCounter::with_memory(temp_dir, memory / 4);
+
+    let mut line = String::new();
+    let mut line_number = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        line_number += 1;
+        let pair = parse_pair(&line, line_number).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if let Some((left_id, right_id)) = pair {
+            left.push(left_id)?;
+            right.push(right_id)?;
+            right_counts.add(right_id)?;
+        }
+    }
+    let runs = left.runs() + right.runs() + right_counts.runs();
+    let reading_memory = left.memory() + right.memory() + right_counts.memory() + line.capacity();
+
+    let (mut left, mut right, mut right_counts) = (left.finish()?, right.finish()?, right_counts.finish()?);
+    let merging_memory = left.memory() + right.memory() + right_counts.memory();
+    let (mut distance, mut similarity): (u128, u128) = (0, 0);
+    let mut right_count = right_counts.next().transpose()?;
+    while let Some(left_id) = left.next().transpose()? {
+        let right_id = right.next().transpose()?.expect("columns have the same length");
+        distance += u128::from(left_id.abs_diff(right_id));
+
+        // The counts are sorted like the left column, so they only ever move forward
+        while let Some((id, count)) = right_count {
+            if id == left_id {
+                similarity += u128::from(left_id) * u128::from(count);
+            }
+            if id >= left_id {
+                break;
+            }
+            right_count = right_counts.next().transpose()?;
+        }
+    }
+
+    Ok(ExternalAnswers { distance, similarity, runs, estimated_peak_memory: reading_memory.max(merging_memory) })
+}
+
+/// The left and right location lists. Only the IDs are kept, never the text they were read from.
+/// IDs may take up all of `u64`; the answers are `u128`, so adding them up cannot overflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocationLists {
+    pub left: Vec<u64>,
+    pub right: Vec<u64>,
+}
+
+impl LocationLists {
+    /// Reads one pair of IDs per line; blank lines are skipped. Malformed lines, negative
+    /// IDs and IDs beyond `u64` are `InvalidData` errors wrapping a [`ParseError`].
+    pub fn read(mut reader: impl BufRead) -> io::Result<LocationLists> {
+        let mut lists = LocationLists::default();
+        let mut line = String::new();
+        let mut line_number = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(lists);
+            }
+            line_number += 1;
+            let pair = parse_pair(&line, line_number).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            if let Some((left, right)) = pair {
+                lists.left.push(left);
+                lists.right.push(right);
+            }
+        }
+    }
+
+    /// Pairs the smallest left ID with the smallest right one and so on, and adds up the distances.
+    /// Sorts both lists in place.
+    pub fn total_distance(&mut self) -> u128 {
+        self.left.sort_unstable();
+        self.right.sort_unstable();
+        self.left.iter().zip(&self.right).map(|(&left, &right)| u128::from(left.abs_diff(right))).sum()
+    }
+
+    /// Every left ID times the number of times it appears in the right list, added up.
+    pub fn similarity_score(&self) -> u128 {
+        similarity(&self.left, &id_counts(&self.right))
+    }
+}
+
+/// The two IDs on a line, `None` for a blank line.
+fn parse_pair(line: &str, line_number: usize) -> Result<Option<(u64, u64)>, ParseError> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+    let ids: Vec<u64> = parse::ints(line).map_err(|err| err.below(line_number))?;
+    match ids[..] {
+        [left, right] => Ok(Some((left, right))),
+        _ => Err(ParseError {
+            line: line_number,
+            column: 1,
+            message: format!("expected two location IDs, found {}", ids.len()),
+        }),
+    }
+}
+
+/// How [`LocationColumns::compare`] compares two columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Sum of the distances between the sorted columns, paired smallest to smallest (part one).
+    Distance,
+    /// Euclidean distance between the sorted columns, paired the same way.
+    EuclideanDistance,
+    /// Every ID of the first column times its count in the second one, added up (part two).
+    /// This is every shared ID times both its counts, so it is symmetric after all.
+    Similarity,
+    /// Jaccard index of the columns as multisets: the IDs they share, counted as often as
+    /// the column that has fewer of them, over all IDs counted as often as the one that has more.
+    Jaccard,
+    /// Spearman's rank correlation of the columns paired by line, with ties ranked by their
+    /// average rank. `NaN` if either column has a single distinct ID.
+    RankCorrelation,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 5] =
+        [Metric::Distance, Metric::EuclideanDistance, Metric::Similarity, Metric::Jaccard, Metric::RankCorrelation];
 
+    /// Name on the command line, e.g. `rank-correlation`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Distance => "distance",
+            Metric::EuclideanDistance => "euclidean",
+            Metric::Similarity => "similarity",
+            Metric::Jaccard => "jaccard",
+            Metric::RankCorrelation => "rank-correlation",
+        }
+    }
+}
 
-    // solution
-    // 2. iterate over the left list and for each number, find the number of times it appears in the right list
-    // 3. multiply the number by the number of times it appears in the right list
-    // 4. add the result to the similarity score
-    // 5. return the similarity score
+impl FromStr for Metric {
+    type Err = String;
 
-    let mut similarity_score = 0;
-    for &num in location_distances.iter() {
-        let count = location_distances.iter().filter(|&x| x.1 == num.0).count();
-        similarity_score += num.0 * count as i32;
+    fn from_str(name: &str) -> Result<Metric, String> {
+        Metric::ALL.into_iter().find(|metric| metric.name() == name).ok_or_else(|| {
+            let names: Vec<&str> = Metric::ALL.iter().map(|metric| metric.name()).collect();
+            format!("unknown metric {}, expected one of {}", name, names.join(", "))
+        })
     }
+}
 
-    println!("Part 2: {}", similarity_score);
+/// Any number of location lists of equal length, one per column of the input.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LocationColumns {
+    pub columns: Vec<Vec<u64>>,
 }
 
-fn main() {
-    let input = include_str!("../input/01.in");
-    // line is a tuple of two number separated by a space - (i32, i32)
-    // make a vector of these tuples
-    let location_distances: Vec<(i32, i32)> = input.lines().map(
-        |line| {
-            let mut split = line.split_whitespace();
-            let a: i32 = split.next().unwrap().parse().unwrap();
-            let b: i32 = split.next().unwrap().parse().unwrap();
-            (a, b)
+impl LocationColumns {
+    /// Reads lines of IDs; the first non-blank line sets the number of columns, and every
+    /// other line must have as many. Errors like [`LocationLists::read`].
+    pub fn read(mut reader: impl BufRead) -> io::Result<LocationColumns> {
+        let mut columns: Vec<Vec<u64>> = Vec::new();
+        let mut line = String::new();
+        let mut line_number = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(LocationColumns { columns });
+            }
+            line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let ids: Vec<u64> = parse::ints(&line)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.below(line_number)))?;
+            if columns.is_empty() {
+                columns = vec![Vec::new(); ids.len()];
+            }
+            if ids.is_empty() || ids.len() != columns.len() {
+                let message = format!("expected {} location IDs, found {}", columns.len().max(1), ids.len());
+                let err = ParseError { line: line_number, column: 1, message };
+                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+            }
+            for (column, id) in columns.iter_mut().zip(ids) {
+                column.push(id);
+            }
         }
-    ).collect();
+    }
+
+    /// `metric` between every pair of columns: row `i`, column `j` compares column `i` with column `j`.
+    pub fn compare(&self, metric: Metric) -> Vec<Vec<f64>> {
+        // Whatever the metric needs from a single column is computed once per column
+        let pair: Box<dyn Fn(usize, usize) -> f64 + '_> = match metric {
+            Metric::Distance | Metric::EuclideanDistance => {
+                let sorted: Vec<Vec<u64>> = self.columns.iter().map(|column| {
+                    let mut sorted = column.clone();
+                    sorted.sort_unstable();
+                    sorted
+                }).collect();
+                Box::new(move |i, j| {
+                    let distances = sorted[i].iter().zip(&sorted[j]).map(|(&a, &b)| a.abs_diff(b));
+                    match metric {
+                        Metric::Distance => distances.map(|distance| distance as f64).sum(),
+                        _ => distances.map(|distance| (distance as f64).powi(2)).sum::<f64>().sqrt(),
+                    }
+                })
+            }
+            Metric::Similarity => {
+                let counts: Vec<HashMap<u64, u64>> = self.columns.iter().map(|column| id_counts(column)).collect();
+                Box::new(move |i, j| similarity(&self.columns[i], &counts[j]) as f64)
+            }
+            Metric::Jaccard => {
+                let counts: Vec<HashMap<u64, u64>> = self.columns.iter().map(|column| id_counts(column)).collect();
+                Box::new(move |i, j| jaccard(&counts[i], &counts[j]))
+            }
+            Metric::RankCorrelation => {
+                let ranks: Vec<Vec<f64>> = self.columns.iter().map(|column| ranks(column)).collect();
+                Box::new(move |i, j| correlation(&ranks[i], &ranks[j]))
+            }
+        };
 
-    part_one(location_distances.clone());
-    part_two(location_distances.clone());
+        let k = self.columns.len();
+        (0..k).map(|i| (0..k).map(|j| pair(i, j)).collect()).collect()
+    }
+}
 
-}
\ No newline at end of file
+fn id_counts(ids: &[u64]) -> HashMap<u64, u64> {
+    let mut counts: HashMap<u64, u64> = HashMap::new();
+    for &id in ids {
+        *counts.entry(id).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn similarity(ids: &[u64], counts: &HashMap<u64, u64>) -> u128 {
+    ids.iter().map(|id| u128::from(*id) * u128::from(counts.get(id).copied().unwrap_or(0))).sum()
+}
+
+/// Shared IDs over all IDs, counting each ID as often as the min and max of its two counts.
+/// Two empty multisets are identical.
+fn jaccard(a: &HashMap<u64, u64>, b: &HashMap<u64, u64>) -> f64 {
+    let shared: u64 = a.iter().map(|(id, &count)| count.min(b.get(id).copied().unwrap_or(0))).sum();
+    let all = a.values().sum::<u64>() + b.values().sum::<u64>() - shared;
+    match all {
+        0 => 1.0,
+        _ => shared as f64 / all as f64,
+    }
+}
+
+/// 1-based rank of every ID in `ids`; equal IDs share the average of their ranks.
+fn ranks(ids: &[u64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..ids.len()).collect();
+    order.sort_unstable_by_key(|&i| ids[i]);
+    let mut ranks = vec![0.0; ids.len()];
+    let mut first = 0;
+    for ties in order.chunk_by(|&a, &b| ids[a] == ids[b]) {
+        // Ranks first + 1 to first + ties.len()
+        let rank = first as f64 + (ties.len() + 1) as f64 / 2.0;
+        for &i in ties {
+            ranks[i] = rank;
+        }
+        first += ties.len();
+    }
+    ranks
+}
+
+/// Pearson correlation of two equally long samples.
+fn correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let covariance: f64 = x.iter().zip(y).map(|(a, b)| (a - mean_x) * (b - mean_y)).sum();
+    let variance_x: f64 = x.iter().map(|a| (a - mean_x).powi(2)).sum();
+    let variance_y: f64 = y.iter().map(|b| (b - mean_y).powi(2)).sum();
+    covariance / (variance_x * variance_y).sqrt()
+}
+
+fn read_lists(input: &str) -> LocationLists {
+    LocationLists::read(input.as_bytes()).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// `size` pairs of five-digit location IDs. About a third of the right IDs repeat
+/// one of the left IDs, so that the similarity score is not zero.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let left: Vec<i64> = (0..size).map(|_| rng.range(10000, 99999)).collect();
+    left.iter()
+        .map(|&id| {
+            let right = match rng.chance(0.3) {
+                true => *rng.choose(&left),
+                false => rng.range(10000, 99999),
+            };
+            format!("{}   {}\n", id, right)
+        })
+        .collect()
+}
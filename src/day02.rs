@@ -1,60 +1,290 @@
-// --- Day 1: Historian Hysteria ---
+use std::fmt;
 
-fn is_safe(report: &Vec<i32>) -> bool {
-    // check if all increasing
-    // check if all decreasing
-    // if not either, return false
+use crate::random::Rng;
 
-    // do a reduce to make a diff of all consecutive numbers
+// --- Day 2: Red-Nosed Reports ---
 
-    let mut diffs = report.windows(2).map(|w| w[1] - w[0]);
-    // check if the sequence is all positive or all negative
-    let is_increasing = diffs.clone().all(|d| d > 0);
-    let is_decreasing = diffs.clone().all(|d| d < 0);
-    let is_diff_between_1_and_3 = diffs.all(|d| d.abs() >= 1 && d.abs() <= 3);
-    return (is_increasing || is_decreasing) && is_diff_between_1_and_3;
+/// Which way the levels of a safe report have to go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either way, as long as the whole report goes the same way.
+    Either,
 }
-fn part_one(reports: Vec<Vec<i32>>) {
+
+/// What makes a report safe. The default is the puzzle's: all increasing or all decreasing
+/// by 1 to 3 at a time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyRules {
+    /// Smallest allowed difference between adjacent levels, ignoring plateaus.
+    pub min_step: i32,
+    pub max_step: i32,
+    pub direction: Direction,
+    /// Whether adjacent levels may be equal regardless of `min_step`.
+    pub allow_plateaus: bool,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        SafetyRules { min_step: 1, max_step: 3, direction: Direction::Either, allow_plateaus: false }
+    }
+}
+
+/// Why a report is unsafe, found at the level it reaches from the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// The level equals the previous one.
+    Plateau,
+    /// The report went one way so far and this level goes the other.
+    DirectionFlip,
+    /// The level goes against the direction required by the rules.
+    WrongDirection,
+    StepTooSmall(i32),
+    StepTooLarge(i32),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Problem::Plateau => write!(f, "plateau"),
+            Problem::DirectionFlip => write!(f, "direction flip"),
+            Problem::WrongDirection => write!(f, "wrong direction"),
+            Problem::StepTooSmall(step) => write!(f, "step of {} too small", step),
+            Problem::StepTooLarge(step) => write!(f, "step of {} too large", step),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Safe,
+    /// `index` is the first level that breaks the rules, coming from the level before it.
+    Unsafe { index: usize, problem: Problem },
+}
+
+impl Verdict {
+    pub fn is_safe(&self) -> bool {
+        *self == Verdict::Safe
+    }
+}
+
+impl SafetyRules {
+    pub fn check(&self, report: &[i32]) -> Verdict {
+        let mut direction = self.direction;
+        for (i, w) in report.windows(2).enumerate() {
+            if let Some(problem) = self.step_problem(w[1] - w[0], &mut direction) {
+                return Verdict::Unsafe { index: i + 1, problem };
+            }
+        }
+        Verdict::Safe
+    }
+
+    /// The fewest levels to remove from `report` to make it safe, as their indices in
+    /// increasing order, or `None` if that takes more than `max_removals`.
+    ///
+    /// Only the level kept last matters for what may follow, so for each level this finds
+    /// the fewest removals that keep it, looking back at most `max_removals + 1` levels for
+    /// the one kept before it: O(n·k) for n levels and k removals.
+    pub fn dampen(&self, report: &[i32], max_removals: usize) -> Option<Vec<usize>> {
+        let directions = match self.direction {
+            Direction::Either => vec![Direction::Increasing, Direction::Decreasing],
+            direction => vec![direction],
+        };
+        directions
+            .into_iter()
+            .filter_map(|direction| SafetyRules { direction, ..*self }.dampen_in_direction(report, max_removals))
+            .min_by_key(|removed| removed.len())
+    }
+
+    fn dampen_in_direction(&self, report: &[i32], max_removals: usize) -> Option<Vec<usize>> {
+        let n = report.len();
+        if n == 0 {
+            return Some(Vec::new());
+        }
+        // removals[i]: fewest removals before level i that leave a safe report ending in it,
+        // previous[i]: the level kept before it then
+        let mut removals: Vec<Option<usize>> = vec![None; n];
+        let mut previous: Vec<Option<usize>> = vec![None; n];
+        for i in 0..n {
+            if i <= max_removals {
+                removals[i] = Some(i);
+            }
+            for j in i.saturating_sub(max_removals + 1)..i {
+                let Some(before) = removals[j] else {
+                    continue;
+                };
+                let total = before + (i - j - 1);
+                let is_better = total <= max_removals && removals[i].is_none_or(|best| total < best);
+                if is_better && self.step_problem(report[i] - report[j], &mut self.direction.clone()).is_none() {
+                    removals[i] = Some(total);
+                    previous[i] = Some(j);
+                }
+            }
+        }
+
+        // Everything after the last level kept is removed as well
+        let last = (0..n)
+            .filter_map(|i| removals[i].map(|before| (before + n - 1 - i, i)))
+            .filter(|&(total, _)| total <= max_removals)
+            .min()
+            .map(|(_, i)| i);
+        let mut kept = vec![false; n];
+        let mut level = Some(last?);
+        while let Some(i) = level {
+            kept[i] = true;
+            level = previous[i];
+        }
+        Some((0..n).filter(|&i| !kept[i]).collect())
+    }
+
+    /// What is wrong with a step of `diff`, if anything. With [`Direction::Either`], the
+    /// first step that is not a plateau sets `direction` for the rest of the report.
+    fn step_problem(&self, diff: i32, direction: &mut Direction) -> Option<Problem> {
+        if diff == 0 {
+            return (!self.allow_plateaus).then_some(Problem::Plateau);
+        }
+        let going = if diff > 0 { Direction::Increasing } else { Direction::Decreasing };
+        if *direction == Direction::Either {
+            *direction = going;
+        } else if *direction != going {
+            return Some(match self.direction {
+                Direction::Either => Problem::DirectionFlip,
+                _ => Problem::WrongDirection,
+            });
+        }
+        match diff.abs() {
+            step if step < self.min_step => Some(Problem::StepTooSmall(step)),
+            step if step > self.max_step => Some(Problem::StepTooLarge(step)),
+            _ => None,
+        }
+    }
+}
+
+/// An unsafe report: its 1-based line in the input, its levels and why it is unsafe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub line: usize,
+    pub report: Vec<i32>,
+    pub index: usize,
+    pub problem: Problem,
+}
+
+impl fmt::Display for Diagnosis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let levels: Vec<String> = self.report.iter().map(|level| level.to_string()).collect();
+        write!(
+            f,
+            "line {}: {}: {} from {} to {} at level {}",
+            self.line,
+            levels.join(" "),
+            self.problem,
+            self.report[self.index - 1],
+            self.report[self.index],
+            self.index + 1
+        )
+    }
+}
+
+/// Every report of `input` that is unsafe under `rules`, with the first problem found in it.
+pub fn diagnose(input: &str, rules: &SafetyRules) -> Vec<Diagnosis> {
+    parse_reports(input)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, report)| match rules.check(&report) {
+            Verdict::Safe => None,
+            Verdict::Unsafe { index, problem } => Some(Diagnosis { line: i + 1, report, index, problem }),
+        })
+        .collect()
+}
+
+fn is_safe(report: &[i32]) -> bool {
+    SafetyRules::default().check(report).is_safe()
+}
+pub fn part_one(input: &str) -> usize {
+    let reports = parse_reports(input);
     let safe_reports = reports.iter().filter(|&r| is_safe(r));
-    println!("Part one: {}", safe_reports.count());
+    safe_reports.count()
 }
 
-fn part_two(reports: Vec<Vec<i32>>) {
+pub fn part_two(input: &str) -> usize {
+    let rules = SafetyRules::default();
+    let reports = parse_reports(input);
+    reports.iter().filter(|r| rules.dampen(r, 1).is_some()).count()
+}
+
+/// Part two by removing every level in turn and checking what is left.
+pub fn part_two_brute_force(input: &str) -> usize {
+    let reports = parse_reports(input);
 
-    fn is_safe_2(report: &Vec<i32>) -> bool {
-        let mut diffs = report.windows(2).map(|w| w[1] - w[0]);
-        // check if the sequence is all positive or all negative
-        let is_increasing = diffs.clone().all(|d| d > 0);
-        let is_decreasing = diffs.clone().all(|d| d < 0);
-        let is_diff_between_1_and_3 = diffs.all(|d| d.abs() >= 1 && d.abs() <= 3);
-        if (is_increasing || is_decreasing) && is_diff_between_1_and_3 {
+    fn is_safe_2(report: &[i32]) -> bool {
+        if is_safe(report) {
             return true;
         }
         // check if removing any level would make the report safe
         for i in 0..report.len() {
-            let mut report_copy = report.clone();
+            let mut report_copy = report.to_vec();
             report_copy.remove(i);
             if is_safe(&report_copy) {
                 return true;
             }
         }
-        return false;
+        false
     }
 
     let safe_reports = reports.iter().filter(|&r| is_safe_2(r));
-    println!("Part two: {}", safe_reports.count());
-
+    safe_reports.count()
 }
 
-fn main() {
-    let input = include_str!("../input/02.in");
-    // line is a tuple of two number separated by a space - (i32, i32)
-    // make a vector of these tuples
-    let reports: Vec<Vec<i32>> = input.lines().map(
-        |r| r.split_whitespace().map(|x| x.parse().unwrap()).collect()
-    ).collect();
+/// Part two without trying every removal: when a report is unsafe in one direction,
+/// the level to remove has to be one of the first pair that breaks the rules.
+pub fn part_two_linear(input: &str) -> usize {
+    fn is_safe_in_direction(report: &[i32], sign: i32) -> Result<(), usize> {
+        match report.windows(2).position(|w| !(1..=3).contains(&((w[1] - w[0]) * sign))) {
+            Some(i) => Err(i),
+            None => Ok(()),
+        }
+    }
 
-    part_one(reports.clone());
-    part_two(reports.clone());
+    fn is_safe_with_dampener(report: &[i32]) -> bool {
+        [1, -1].into_iter().any(|sign| match is_safe_in_direction(report, sign) {
+            Ok(()) => true,
+            Err(i) => (i..=i + 1).any(|removed| {
+                let mut report_copy = report.to_vec();
+                report_copy.remove(removed);
+                is_safe_in_direction(&report_copy, sign).is_ok()
+            }),
+        })
+    }
 
-}
\ No newline at end of file
+    let reports = parse_reports(input);
+    reports.iter().filter(|r| is_safe_with_dampener(r)).count()
+}
+
+/// `size` reports of 5 to 8 levels. Most are safe or close to it: each difference
+/// follows the report's direction, with an occasional one that breaks the rules.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = Vec::with_capacity(size);
+    for _ in 0..size {
+        let sign = *rng.choose(&[1, -1]);
+        let mut level = rng.range(10, 90);
+        let mut report = vec![level];
+        for _ in 1..rng.range(5, 8) {
+            let diff = match rng.chance(0.1) {
+                true => rng.range(-5, 5),
+                false => sign * rng.range(1, 3),
+            };
+            level = (level + diff).clamp(1, 99);
+            report.push(level);
+        }
+        lines.push(report.iter().map(|level| level.to_string()).collect::<Vec<_>>().join(" "));
+    }
+    lines.join("\n") + "\n"
+}
+
+fn parse_reports(input: &str) -> Vec<Vec<i32>> {
+    // every line is a report: a list of levels separated by spaces
+    input.lines().map(
+        |r| r.split_whitespace().map(|x| x.parse().unwrap()).collect()
+    ).collect()
+}
@@ -1,81 +1,351 @@
-use regex::Regex;
+use std::io::{self, Read};
+
+use crate::random::Rng;
 // --- Day 3: Mull It Over ---
 
-fn remove_sections_between_dont_and_do(text: &str) -> String {
-    let mut result = String::new();
-    let mut i = 0;
-    let chars: Vec<char> = text.chars().collect();
-    let len = chars.len();
-
-    // Whether we're skipping text (i.e., between don't() and the next do()).
-    let mut skip_mode = false;
-
-    while i < len {
-        // Try to match "don't()" or "do()" at position i.
-        if !skip_mode && text[i..].starts_with("don't()") {
-            // We just encountered "don't()" -> enter skip mode.
-            skip_mode = true;
-            i += "don't()".len();
-        } else if skip_mode && text[i..].starts_with("do()") {
-            // We just encountered "do()" while skipping -> exit skip mode.
-            skip_mode = false;
-            i += "do()".len();
-        } else {
-            // We either copy (not skipping) or skip (skip_mode == true).
-            if !skip_mode {
-                result.push(chars[i]);
+/// Most arguments an instruction can take.
+pub const MAX_ARGS: usize = 4;
+
+/// What an argument of an instruction looks like: a run of digits, optionally with a `-`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operand {
+    pub min_digits: usize,
+    pub max_digits: usize,
+    /// Whether a leading `-` is allowed.
+    pub signed: bool,
+}
+
+impl Operand {
+    /// The operands of `mul`: 1 to 3 digits.
+    pub const NUMBER: Operand = Operand::digits(1, 3);
+
+    pub const fn digits(min_digits: usize, max_digits: usize) -> Operand {
+        Operand { min_digits, max_digits, signed: false }
+    }
+
+    pub const fn signed(self) -> Operand {
+        Operand { signed: true, ..self }
+    }
+
+    /// The value at the start of `bytes`, with the number of bytes it takes.
+    fn parse(&self, bytes: &[u8]) -> Option<(i64, usize)> {
+        let sign = usize::from(self.signed && bytes.first() == Some(&b'-'));
+        let digits = bytes[sign..].iter().take(self.max_digits + 1).take_while(|byte| byte.is_ascii_digit()).count();
+        if digits < self.min_digits.max(1) || digits > self.max_digits {
+            return None;
+        }
+        let value = bytes[sign..sign + digits].iter().fold(0, |value, digit| value * 10 + (digit - b'0') as i64);
+        Some((if sign == 1 { -value } else { value }, sign + digits))
+    }
+}
+
+/// What the interpreter keeps between instructions of the puzzle's set. Sets of other
+/// instructions can keep a state of their own instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct State {
+    /// Whether values count towards `enabled_total`; `do()` and `don't()` switch it.
+    pub enabled: bool,
+    /// Every value produced (part one). Wide enough for any number of products of
+    /// 3-digit operands, e.g. in memory dumps of many gigabytes.
+    pub total: i128,
+    /// Only the values produced while enabled (part two).
+    pub enabled_total: i128,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        State { enabled: true, total: 0, enabled_total: 0 }
+    }
+}
+
+impl State {
+    /// Adds the result of an instruction to the totals.
+    pub fn produce(&mut self, value: i128) {
+        self.total += value;
+        if self.enabled {
+            self.enabled_total += value;
+        }
+    }
+}
+
+/// What an instruction does with its arguments to the interpreter's state `S`.
+pub type Effect<S = State> = fn(&mut S, &[i64]);
+
+#[derive(Debug, Clone)]
+pub struct InstructionDef<S = State> {
+    pub name: String,
+    /// One pattern per argument; an instruction without arguments is written `name()`.
+    pub operands: Vec<Operand>,
+    pub effect: Effect<S>,
+}
+
+/// The instructions the lexer looks for and the interpreter runs on a state `S`. The
+/// default set is the puzzle's: `mul(a,b)`, `do()` and `don't()` on a [`State`].
+#[derive(Debug, Clone)]
+pub struct InstructionSet<S = State> {
+    instructions: Vec<InstructionDef<S>>,
+}
+
+impl Default for InstructionSet<State> {
+    fn default() -> Self {
+        InstructionSet::<State>::empty()
+            .with("mul", &[Operand::NUMBER, Operand::NUMBER], |state, args| state.produce(args[0] as i128 * args[1] as i128))
+            .with("do", &[], |state, _| state.enabled = true)
+            .with("don't", &[], |state, _| state.enabled = false)
+    }
+}
+
+impl<S> InstructionSet<S> {
+    pub fn empty() -> InstructionSet<S> {
+        InstructionSet { instructions: Vec::new() }
+    }
+
+    /// Adds an instruction, e.g. `add(a,b)`. Names are matched exactly, so instructions
+    /// may share prefixes such as `do` and `don't`.
+    pub fn with(mut self, name: &str, operands: &[Operand], effect: Effect<S>) -> InstructionSet<S> {
+        assert!(!name.is_empty() && !name.contains('('), "invalid instruction name `{}`", name);
+        assert!(operands.len() <= MAX_ARGS, "`{}` takes more than {} arguments", name, MAX_ARGS);
+        assert!(
+            operands.iter().all(|operand| operand.max_digits <= 18),
+            "`{}` takes operands of more than 18 digits, which do not fit in an i64",
+            name
+        );
+        self.instructions.push(InstructionDef { name: name.to_string(), operands: operands.to_vec(), effect });
+        self
+    }
+
+    pub fn instructions(&self) -> &[InstructionDef<S>] {
+        &self.instructions
+    }
+
+    /// Length in bytes of the longest instruction text, such as `mul(123,456)`.
+    pub fn max_len(&self) -> usize {
+        self.instructions
+            .iter()
+            .map(|instruction| {
+                let operands: usize = instruction.operands.iter().map(|operand| operand.signed as usize + operand.max_digits).sum();
+                let commas = instruction.operands.len().saturating_sub(1);
+                instruction.name.len() + "()".len() + operands + commas
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The instruction starting at the start of `bytes`, with its arguments and length.
+    fn match_at(&self, bytes: &[u8]) -> Option<(usize, Args, usize)> {
+        self.instructions.iter().enumerate().find_map(|(index, instruction)| {
+            let mut len = instruction.name.len();
+            if !bytes.starts_with(instruction.name.as_bytes()) || bytes.get(len) != Some(&b'(') {
+                return None;
             }
-            i += 1;
+            len += 1;
+            let mut args = Args::default();
+            for (i, operand) in instruction.operands.iter().enumerate() {
+                if i > 0 {
+                    if bytes.get(len) != Some(&b',') {
+                        return None;
+                    }
+                    len += 1;
+                }
+                let (value, operand_len) = operand.parse(&bytes[len..])?;
+                args.push(value);
+                len += operand_len;
+            }
+            (bytes.get(len) == Some(&b')')).then_some((index, args, len + 1))
+        })
+    }
+}
+
+/// Arguments of one instruction, kept inline.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Args {
+    values: [i64; MAX_ARGS],
+    len: usize,
+}
+
+impl Args {
+    fn push(&mut self, value: i64) {
+        self.values[self.len] = value;
+        self.len += 1;
+    }
+
+    pub fn as_slice(&self) -> &[i64] {
+        &self.values[..self.len]
+    }
+}
+
+/// An instruction of an [`InstructionSet`], by index, with its arguments and the byte
+/// offset in the memory where it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token {
+    pub offset: usize,
+    pub instruction: usize,
+    pub args: Args,
+}
+
+/// Finds the instructions in corrupted memory in a single pass, skipping everything that
+/// does not follow their grammar exactly: no spaces, and operands as declared.
+/// Works on bytes, so any UTF-8 in the memory is just skipped.
+pub struct Lexer<'a, S = State> {
+    memory: &'a [u8],
+    offset: usize,
+    /// Instructions starting at or after this offset are left for the next chunk of a stream.
+    end: usize,
+    /// Offset of `memory` in the whole stream.
+    base: usize,
+    set: &'a InstructionSet<S>,
+    /// Bytes that start an instruction name, to skip everything else quickly.
+    starts: [bool; 256],
+}
+
+impl<'a, S> Lexer<'a, S> {
+    pub fn new(memory: &'a str, set: &'a InstructionSet<S>) -> Lexer<'a, S> {
+        Lexer::chunk(memory.as_bytes(), 0, memory.len(), set)
+    }
+
+    /// Lexes the instructions starting before `end` in a chunk of a stream that starts at offset `base`.
+    fn chunk(memory: &'a [u8], base: usize, end: usize, set: &'a InstructionSet<S>) -> Lexer<'a, S> {
+        let mut starts = [false; 256];
+        for instruction in &set.instructions {
+            starts[instruction.name.as_bytes()[0] as usize] = true;
         }
+        Lexer { memory, offset: 0, end, base, set, starts }
     }
-    result
 }
 
-fn mull_it_over(text: &str) -> i32 {
-    let text_no_spaces = text.replace(" ", "");
+impl<S> Iterator for Lexer<'_, S> {
+    type Item = Token;
 
-    let re = Regex::new(r"mul\(\d+,\d+\)").unwrap();
+    fn next(&mut self) -> Option<Token> {
+        while self.offset < self.end {
+            let offset = self.offset;
+            self.offset += 1;
+            if !self.starts[self.memory[offset] as usize] {
+                continue;
+            }
+            if let Some((instruction, args, len)) = self.set.match_at(&self.memory[offset..]) {
+                self.offset = offset + len;
+                return Some(Token { offset: self.base + offset, instruction, args });
+            }
+        }
+        None
+    }
+}
 
-    // 4. Initialize accumulator
-    let mut mul_res: i32 = 0;
+/// Runs instructions of a set on a state, counting how often each one was executed.
+pub struct Interpreter<'a, S = State> {
+    set: &'a InstructionSet<S>,
+    pub state: S,
+    counts: Vec<u64>,
+}
 
-    // 5. Find all matches in the file and sum them up
-    for mat in re.find_iter(&text_no_spaces) {
-        // mat.as_str() is something like "mul(3,10)"
-        let substring = mat.as_str();
+impl<'a, S: Default> Interpreter<'a, S> {
+    pub fn new(set: &'a InstructionSet<S>) -> Interpreter<'a, S> {
+        Interpreter::with_state(set, S::default())
+    }
+}
+
+impl<'a, S> Interpreter<'a, S> {
+    pub fn with_state(set: &'a InstructionSet<S>, state: S) -> Interpreter<'a, S> {
+        Interpreter { set, state, counts: vec![0; set.instructions.len()] }
+    }
 
-        // Remove "mul(" (4 chars) and the trailing ")" (1 char):
-        // e.g. "mul(3,10)" -> inside: "3,10"
-        let inside = &substring[4..(substring.len() - 1)];
+    pub fn execute(&mut self, token: &Token) {
+        (self.set.instructions[token.instruction].effect)(&mut self.state, token.args.as_slice());
+        self.counts[token.instruction] += 1;
+    }
 
-        // Split at the comma -> ["3", "10"]
-        let parts: Vec<&str> = inside.split(',').collect();
+    pub fn run(&mut self, tokens: impl IntoIterator<Item = Token>) -> &S {
+        for token in tokens {
+            self.execute(&token);
+        }
+        &self.state
+    }
 
-        // Parse the two numbers
-        let x: i32 = parts[0].parse().expect("Invalid integer");
-        let y: i32 = parts[1].parse().expect("Invalid integer");
+    /// Runs the instructions in `reader`, reading `chunk_size` bytes at a time, so memory of
+    /// any size takes constant memory. The end of each chunk is carried over to the next
+    /// one, so that instructions split between chunks are still found.
+    pub fn run_stream(&mut self, reader: &mut dyn Read, chunk_size: usize) -> io::Result<&S> {
+        // An instruction starting before this many bytes from the end of the buffer lies
+        // entirely inside it, including the digit after an operand that rules out a longer one
+        let tail = self.set.max_len().saturating_sub(1);
+        let mut buffer: Vec<u8> = Vec::with_capacity(chunk_size + tail);
+        let mut base = 0;
+        loop {
+            let carried = buffer.len();
+            buffer.resize(carried + chunk_size.max(1), 0);
+            let read = loop {
+                match reader.read(&mut buffer[carried..]) {
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    result => break result?,
+                }
+            };
+            buffer.truncate(carried + read);
 
-        // Multiply and accumulate
-        mul_res += x * y;
+            let end = if read == 0 { buffer.len() } else { buffer.len().saturating_sub(tail) };
+            let mut lexer = Lexer::chunk(&buffer, base, end, self.set);
+            for token in lexer.by_ref() {
+                self.execute(&token);
+            }
+            let scanned = lexer.offset;
+            buffer.drain(..scanned);
+            base += scanned;
+            if read == 0 {
+                return Ok(&self.state);
+            }
+        }
+    }
+
+    /// Every instruction of the set with the number of times it was executed.
+    pub fn counts(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.set.instructions.iter().zip(&self.counts).map(|(instruction, &count)| (instruction.name.as_str(), count))
     }
-    return mul_res;
 }
 
-fn part_one(text: &str) {
-    let res = mull_it_over(text);
-    println!("Part one: {:?}", res);
+/// Runs the instructions of `set` found in `memory` on a fresh state, e.g. computing both
+/// parts at once with the default set.
+pub fn interpret<S: Default>(memory: &str, set: &InstructionSet<S>) -> S {
+    let mut interpreter = Interpreter::new(set);
+    interpreter.run(Lexer::new(memory, set));
+    interpreter.state
 }
 
-fn part_two(text: &str) {
-    let res = mull_it_over(remove_sections_between_dont_and_do(text).as_str());
-    print!("Part two: {:?}", res);
+/// Both answers for memory read from `reader`, with how often each instruction ran.
+pub fn solve_stream(reader: &mut dyn Read) -> io::Result<(State, Vec<(String, u64)>)> {
+    const CHUNK_SIZE: usize = 1 << 16;
+    let set = InstructionSet::default();
+    let mut interpreter = Interpreter::new(&set);
+    let state = *interpreter.run_stream(reader, CHUNK_SIZE)?;
+    Ok((state, interpreter.counts().map(|(name, count)| (name.to_string(), count)).collect()))
 }
 
-fn main() {
-    let input = include_str!("../input/03.in");
-    // line is a tuple of two number separated by a space - (i32, i32)
-    // make a vector of these tuples
-    part_one(input.clone());
-    part_two(input.clone());
+pub fn part_one(text: &str) -> i128 {
+    interpret(text, &InstructionSet::default()).total
+}
 
+pub fn part_two(text: &str) -> i128 {
+    interpret(text, &InstructionSet::default()).enabled_total
+}
+
+/// `size` fragments of corrupted memory: valid `mul(a,b)` instructions, `do()` and `don't()`,
+/// near misses such as `mul(4*` or `mul[3,7]`, and junk characters in between.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const JUNK: &[char] = &['%', '@', '#', '$', '^', '&', '*', '!', '?', '[', ']', '{', '}', '<', '>', '(', ')', ',', ':', ';', '\'', '/', '+', '-', '~', ' '];
+    const WORDS: &[&str] = &["why()", "who()", "what()", "when()", "where()", "from()", "select()", "how()"];
+    let mut memory = String::new();
+    for _ in 0..size {
+        let a = rng.range(1, 999);
+        let b = rng.range(1, 999);
+        match rng.below(8) {
+            0..=2 => memory += &format!("mul({},{})", a, b),
+            3 => memory += *rng.choose(&["do()", "don't()"]),
+            4 => memory += *rng.choose(&["mul(4*", "mul[3,7]", "mul ( 2 , 4 )", "mul(6,9!", "?(12,34)", "mul(32,64]"]),
+            5 => memory += *rng.choose(WORDS),
+            _ => {}
+        }
+        for _ in 0..rng.range(0, 4) {
+            memory.push(*rng.choose(JUNK));
+        }
+    }
+    memory + "\n"
 }
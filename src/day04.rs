@@ -1,171 +1,251 @@
-use std::ops::Add;
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+use crate::aho_corasick::{AhoCorasick, Match};
+use crate::parse::{self, ParseError};
+use crate::random::Rng;
 
 // --- Day 4: Ceres Search ---
-fn collect_edge_diagonals(lines: &[String], min_len: usize) -> Vec<String> {
-    let height = lines.len();
-    if height == 0 {
-        return Vec::new();
-    }
-    let width = lines[0].len();
 
-    let mut results = Vec::new();
+/// The eight directions a word can be written in, as `(row, col)` steps.
+pub const DIRECTIONS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
 
-    // == DOWN-RIGHT (↘) ==
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Let words continue on the opposite edge of the grid, as if it were a torus.
+    pub wrap: bool,
+    /// Report words that share cells with an earlier match in the same direction on the same
+    /// line. Without this, the leftmost (then longest) match wins.
+    pub overlapping: bool,
+}
 
-    // 1) Start from left edge: (row in 0..height, col = 0)
-    for start_row in 0..height {
-        let diagonal = gather_down_right(lines, start_row, 0);
-        if diagonal.len() >= min_len {
-            results.push(diagonal);
-        }
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions { wrap: false, overlapping: true }
     }
+}
 
-    // 2) Start from top edge: (row = 0, col in 0..width)
-    //    (We already did col = 0 in the previous loop, so if you want
-    //     to avoid duplicating the (0,0) start, do col in 1..width).
-    for start_col in 1..width {
-        let diagonal = gather_down_right(lines, 0, start_col);
-        if diagonal.len() >= min_len {
-            results.push(diagonal);
+/// A word found in the grid: `word` is its index in the word list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WordMatch {
+    pub row: usize,
+    pub col: usize,
+    pub direction: (isize, isize),
+    pub word: usize,
+}
+
+/// Finds `words` in a rectangular grid of letters, reading every line of the grid in each of
+/// the eight directions once and matching all words at the same time.
+/// Matches of a word that cover the same cells in the same order are reported once, in the
+/// first direction found: a one-letter word is found once per cell, not once per direction.
+pub fn word_search<S: AsRef<str>>(grid: &[Vec<char>], words: &[S], options: SearchOptions) -> Vec<WordMatch> {
+    let automaton = AhoCorasick::new(words);
+    let mut found = Vec::new();
+    let mut seen: HashSet<(usize, Vec<(usize, usize)>)> = HashSet::new();
+    for direction in DIRECTIONS {
+        let lines = match options.wrap {
+            false => grid_lines(grid, direction),
+            true => grid_cycles(grid, direction),
+        };
+        for line in lines {
+            // A cycle is read around once more, for words that cross the place where it was cut
+            let extra = match options.wrap {
+                true => automaton.max_len().saturating_sub(1),
+                false => 0,
+            };
+            let text = line.iter().cycle().take(line.len() + extra).map(|&(row, col)| grid[row][col]);
+            let mut matches: Vec<Match> =
+                automaton.find_all(text).into_iter().filter(|found| found.start < line.len()).collect();
+            if !options.overlapping {
+                matches.sort_by_key(|found| (found.start, Reverse(found.len)));
+                let mut end = 0;
+                matches.retain(|found| {
+                    let keep = found.start >= end;
+                    if keep {
+                        end = found.start + found.len;
+                    }
+                    keep
+                });
+            }
+            for found_match in matches {
+                let cells = line.iter().cycle().skip(found_match.start).take(found_match.len).copied().collect();
+                if seen.insert((found_match.pattern, cells)) {
+                    let (row, col) = line[found_match.start];
+                    found.push(WordMatch { row, col, direction, word: found_match.pattern });
+                }
+            }
         }
     }
+    found
+}
 
-    // == DOWN-LEFT (↙) ==
-
-    // 1) Start from right edge: (row in 0..height, col = width - 1)
-    if width > 0 {
-        for start_row in 0..height {
-            let diagonal = gather_down_left(lines, start_row, width - 1);
-            if diagonal.len() >= min_len {
-                results.push(diagonal);
+/// The cells of every line through the grid in `direction`, from edge to edge.
+fn grid_lines(grid: &[Vec<char>], (dr, dc): (isize, isize)) -> Vec<Vec<(usize, usize)>> {
+    let (height, width) = (grid.len(), grid.first().map_or(0, Vec::len));
+    let cell = |row: usize, col: usize, steps: isize| {
+        let row = row.checked_add_signed(dr * steps).filter(|&row| row < height)?;
+        let col = col.checked_add_signed(dc * steps).filter(|&col| col < width)?;
+        Some((row, col))
+    };
+    let mut lines = Vec::new();
+    for row in 0..height {
+        for col in 0..width {
+            // Lines start at the cells with nothing before them
+            if cell(row, col, -1).is_none() {
+                lines.push((0..).map_while(|steps| cell(row, col, steps)).collect());
             }
         }
     }
+    lines
+}
 
-    // 2) Start from top edge: (row = 0, col in 0..width)
-    //    (Again, skip col=width-1 if you want to avoid duplication.)
-    if height > 0 {
-        for start_col in 0..width - 1 {
-            let diagonal = gather_down_left(lines, 0, start_col);
-            if diagonal.len() >= min_len {
-                results.push(diagonal);
+/// The cells of every cycle through the grid in `direction` when leaving it on one edge
+/// means coming back on the opposite one.
+fn grid_cycles(grid: &[Vec<char>], (dr, dc): (isize, isize)) -> Vec<Vec<(usize, usize)>> {
+    let (height, width) = (grid.len(), grid.first().map_or(0, Vec::len));
+    let mut seen = vec![vec![false; width]; height];
+    let mut cycles = Vec::new();
+    for row in 0..height {
+        for col in 0..width {
+            let mut cycle = Vec::new();
+            let (mut r, mut c) = (row, col);
+            while !seen[r][c] {
+                seen[r][c] = true;
+                cycle.push((r, c));
+                r = (r as isize + dr).rem_euclid(height as isize) as usize;
+                c = (c as isize + dc).rem_euclid(width as isize) as usize;
+            }
+            if !cycle.is_empty() {
+                cycles.push(cycle);
             }
         }
     }
-
-    results
+    cycles
 }
 
-/// Gathers characters along a down-right diagonal (↘) starting at (row, col).
-fn gather_down_right(lines: &[String], mut row: usize, mut col: usize) -> String {
-    let height = lines.len();
-    let width = lines[0].len();
-    let mut result = String::new();
+fn parse_grid(input: &str) -> Vec<Vec<char>> {
+    parse::grid_of(input, Some).unwrap()
+}
 
-    while row < height && col < width {
-        if let Some(ch) = lines[row].chars().nth(col) {
-            result.push(ch);
-        }
-        row += 1;
-        col += 1;
-    }
+pub fn part_one(input: &str) -> usize {
+    word_search(&parse_grid(input), &["XMAS"], SearchOptions::default()).len()
+}
 
-    result
+/// A small 2D pattern of letters where `.` matches anything, e.g. the X-MAS `M.S/.A./M.S`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Template {
+    /// `None` for wildcard cells.
+    pub cells: Vec<Vec<Option<char>>>,
 }
 
-/// Gathers characters along a down-left diagonal (↙) starting at (row, col).
-fn gather_down_left(lines: &[String], mut row: usize, mut col: usize) -> String {
-    let height = lines.len();
-    let width = lines[0].len();
-    let mut result = String::new();
+impl Template {
+    /// Reads a template from rows separated by newlines or `/`; spaces around rows are ignored.
+    pub fn parse(text: &str) -> Result<Template, ParseError> {
+        let rows: Vec<&str> = text.split(['/', '\n']).map(str::trim).filter(|row| !row.is_empty()).collect();
+        let cells = parse::grid_of(&rows.join("\n"), |ch| Some((ch != '.').then_some(ch)))?;
+        Ok(Template { cells })
+    }
 
-    while row < height && col < width {
-        if let Some(ch) = lines[row].chars().nth(col) {
-            result.push(ch);
-        }
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
 
-        // Move down-left
-        if col == 0 {
-            break;
-        }
-        row += 1;
-        col -= 1;
+    pub fn cols(&self) -> usize {
+        self.cells.first().map_or(0, Vec::len)
     }
 
-    result
-}
+    /// The template turned a quarter clockwise.
+    pub fn rotate(&self) -> Template {
+        let cells = (0..self.cols()).map(|col| (0..self.rows()).rev().map(|row| self.cells[row][col]).collect()).collect();
+        Template { cells }
+    }
 
-fn part_one(lines: Vec<&str>) {
-    let lines_orig: Vec<String> = lines.iter().map(|x| x.to_string()).collect();
-    let lines_rev: Vec<String> = lines.iter().map(|x| x.chars().rev().collect()).collect();
+    /// The template mirrored left to right.
+    pub fn reflect(&self) -> Template {
+        Template { cells: self.cells.iter().map(|row| row.iter().rev().copied().collect()).collect() }
+    }
 
-    let mut lines_vertical: Vec<String> = Vec::new();
-    for j in 0..lines[0].len() {
-        let mut tmp_line = String::new();
-        for i in 0..lines.len() {
-            if let Some(c) = lines[i].chars().nth(j) {
-                tmp_line = tmp_line.add(&c.to_string());
+    /// The distinct templates among all rotations and reflections, this one first.
+    pub fn variants(&self) -> Vec<Template> {
+        let mut variants: Vec<Template> = Vec::with_capacity(8);
+        for mut variant in [self.clone(), self.reflect()] {
+            for _ in 0..4 {
+                let next = variant.rotate();
+                if !variants.contains(&variant) {
+                    variants.push(variant);
+                }
+                variant = next;
             }
         }
-        lines_vertical.push(tmp_line);
+        variants
     }
-    let lines_vertical_rev= lines_vertical.iter().map(|x| x.chars().rev().collect()).collect();
-
-    let lines_diagonal = collect_edge_diagonals(&lines_orig, 4);
 
-    let lines_diagonal_rev = lines_diagonal.iter().map(|x| x.chars().rev().collect()).collect();
+    /// Whether the template matches with its top-left corner at `(row, col)` of the grid.
+    fn matches_at(&self, grid: &[Vec<char>], row: usize, col: usize) -> bool {
+        self.cells.iter().enumerate().all(|(i, cells)| {
+            cells.iter().enumerate().all(|(j, cell)| match cell {
+                None => true,
+                Some(letter) => grid.get(row + i).and_then(|grid_row| grid_row.get(col + j)) == Some(letter),
+            })
+        })
+    }
+}
 
-    let lines_all = vec![lines_orig, lines_rev, lines_vertical, lines_vertical_rev, lines_diagonal, lines_diagonal_rev];
+/// A place where a template matches: `variant` is the index in [`Template::variants`] of the
+/// rotation or reflection that matched there, with its top-left corner at `(row, col)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PatternMatch {
+    pub row: usize,
+    pub col: usize,
+    pub variant: usize,
+}
 
-    let mut ceres_count = 0;
-    for tmp_lines in lines_all {
-        for line in tmp_lines {
-            for i in 0..line.len()-3 {
-                if line[i..i+4].eq("XMAS") {
-                    ceres_count += 1;
-                    // println!("Found Ceres at line: {}", line);
+/// Every place where `template`, turned or mirrored in any way, matches the grid.
+pub fn find_pattern(grid: &[Vec<char>], template: &Template) -> Vec<PatternMatch> {
+    let mut found = Vec::new();
+    if template.rows() == 0 || template.cols() == 0 {
+        return found;
+    }
+    for (variant, shape) in template.variants().iter().enumerate() {
+        for row in 0..(grid.len() + 1).saturating_sub(shape.rows()) {
+            for col in 0..(grid[row].len() + 1).saturating_sub(shape.cols()) {
+                if shape.matches_at(grid, row, col) {
+                    found.push(PatternMatch { row, col, variant });
                 }
             }
         }
     }
-    println!("Part one {:?}", ceres_count)
+    found
 }
 
-fn is_x_mas(lines: &Vec<Vec<char>>, i: usize, j: usize) -> bool {
-    // Check bounds to avoid out-of-bounds errors
-    if i + 2 >= lines.len() || j + 2 >= lines[i].len() {
-        return false;
-    }
-
-    // Top-left MAS can be either forward (MAS) or backward (SAM)
-    let top_left_mas = (lines[i][j] == 'M' && lines[i + 1][j + 1] == 'A' && lines[i + 2][j + 2] == 'S') ||
-        (lines[i][j] == 'S' && lines[i + 1][j + 1] == 'A' && lines[i + 2][j + 2] == 'M');
-
-    // Bottom-left MAS can be either forward (MAS) or backward (SAM)
-    let bottom_left_mas = (lines[i + 2][j] == 'M' && lines[i + 1][j + 1] == 'A' && lines[i][j + 2] == 'S') ||
-        (lines[i + 2][j] == 'S' && lines[i + 1][j + 1] == 'A' && lines[i][j + 2] == 'M');
-
-    // Both top-left and bottom-left MAS must be present to form an X-MAS
-    top_left_mas && bottom_left_mas
+pub fn part_two(input: &str) -> usize {
+    let x_mas = Template::parse("M.S / .A. / M.S").unwrap();
+    find_pattern(&parse_grid(input), &x_mas).len()
 }
 
-fn part_two(lines: Vec<&str>) {
-    let char_lines: Vec<Vec<char>> = lines.iter().map(|line| line.chars().collect()).collect();
-    let mut x_mas_count = 0;
-
-    for i in 0..char_lines.len() - 2 {
-        for j in 0..char_lines[i].len() - 2 {
-            if is_x_mas(&char_lines, i, j) {
-                x_mas_count += 1;
+/// A `size x size` grid of the letters X, M, A and S, with some `XMAS` planted in all eight
+/// directions and some X-shaped `MAS` pairs.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(4);
+    let mut grid: Vec<Vec<char>> = (0..size).map(|_| (0..size).map(|_| *rng.choose(&['X', 'M', 'A', 'S'])).collect()).collect();
+    let in_grid = |row: isize, col: isize| row >= 0 && col >= 0 && row < size as isize && col < size as isize;
+
+    for _ in 0..size {
+        let (row, col) = (rng.index(size) as isize, rng.index(size) as isize);
+        let (dr, dc) = *rng.choose(&DIRECTIONS);
+        if in_grid(row + 3 * dr, col + 3 * dc) {
+            for (i, letter) in "XMAS".chars().enumerate() {
+                grid[(row + i as isize * dr) as usize][(col + i as isize * dc) as usize] = letter;
             }
         }
     }
+    for _ in 0..size / 2 {
+        let (row, col) = (rng.index(size - 2), rng.index(size - 2));
+        let (a, b) = (*rng.choose(&[('M', 'S'), ('S', 'M')]), *rng.choose(&[('M', 'S'), ('S', 'M')]));
+        grid[row + 1][col + 1] = 'A';
+        (grid[row][col], grid[row + 2][col + 2]) = a;
+        (grid[row + 2][col], grid[row][col + 2]) = b;
+    }
 
-    println!("Part two {:?}", x_mas_count)
-}
-
-fn main() {
-    let lines: Vec<&str> = include_str!("../input/04.in").lines().collect();
-    part_one(lines.clone());
-    part_two(lines.clone());
+    grid.iter().map(|line| line.iter().collect::<String>() + "\n").collect()
 }
@@ -1,27 +1,13 @@
-use std::collections::HashMap;
+use crate::graph::DiGraph;
+use crate::parse::{self, ParseError};
+use crate::random::Rng;
 // --- Day 5: Print Queue ---
 
-fn is_print_order_safe(print_order: &[i32], page_number_preds: &HashMap<i32, Vec<i32>>) -> bool {
-    // Compare every pair (i, j) where j > i
-    for i in 0..print_order.len() {
-        let page_number = print_order[i];
-        for j in i + 1..print_order.len() {
-            let page_number_next = print_order[j];
-            // If successor has a predecessor list
-            if let Some(preds) = page_number_preds.get(&page_number_next) {
-                // And if that list contains print_order[i], it's unsafe
-                if preds.contains(&page_number) {
-                    return false;
-                }
-            }
-        }
-    }
-    true
-}
-fn part_one(print_orders: Vec<Vec<i32>>, page_number_preds: HashMap<i32, Vec<i32>>) {
+pub fn part_one(input: &str) -> i32 {
+    let (print_orders, page_order_rules) = parse_print_queue(input);
     let safe_print_orders: Vec<&Vec<i32>> = print_orders
         .iter()
-        .filter(|r| is_print_order_safe(r, &page_number_preds))
+        .filter(|r| page_order_rules.respects_order(r))
         .collect();
 
     let sum_of_middle: i32 = safe_print_orders
@@ -34,42 +20,27 @@ fn part_one(print_orders: Vec<Vec<i32>>, page_number_preds: HashMap<i32, Vec<i32
         .copied()
         .sum();
 
-    println!("Part one: {:?}", sum_of_middle);
+    sum_of_middle
 }
 
 
-fn part_two(print_orders: Vec<Vec<i32>>, page_number_preds: HashMap<i32, Vec<i32>>) {
+pub fn part_two(input: &str) -> i32 {
+    let (print_orders, page_order_rules) = parse_print_queue(input);
     let nonsafe_print_orders: Vec<&Vec<i32>> = print_orders
         .iter()
-        .filter(|r| !is_print_order_safe(r, &page_number_preds))
+        .filter(|r| !page_order_rules.respects_order(r))
         .collect();
 
-    fn fix_order(order: &Vec<i32>, page_number_preds: &HashMap<i32, Vec<i32>>) -> Vec<i32> {
-        let mut fixed_order = order.clone();
-        println!("old: {:?}", fixed_order);
-        let mut swapped = true;
-        while swapped {
-            swapped = false;
-            for i in 0..(fixed_order.len().saturating_sub(1)) {
-                let j = i + 1;
-                let page_number = fixed_order[i];
-                let page_number_next = fixed_order[j];
-                if let Some(preds) = page_number_preds.get(&page_number_next) {
-                    if preds.contains(&page_number) {
-                        fixed_order.swap(i, j);
-                        swapped = true;
-                    }
-                }
-            }
-        }
-        println!("new: {:?}", fixed_order);
-        println!("###################");
-        fixed_order
-    }
-
+    // Only the rules between pages of the same update count; pages without a rule between
+    // them keep their order from the update
     let fixed_nonsafe_print_orders: Vec<Vec<i32>> = nonsafe_print_orders
         .iter()
-        .map(|order| fix_order(&order, &page_number_preds))
+        .map(|&order| {
+            page_order_rules.topological_sort(order).unwrap_or_else(|cycle| {
+                let pages: Vec<String> = cycle.iter().map(|page| page.to_string()).collect();
+                panic!("update {:?} cannot be ordered: the rules put pages {} in a cycle", order, pages.join(" -> "))
+            })
+        })
         .collect();
 
     let sum_of_middle: i32 = fixed_nonsafe_print_orders
@@ -82,47 +53,137 @@ fn part_two(print_orders: Vec<Vec<i32>>, page_number_preds: HashMap<i32, Vec<i32
         .copied()
         .sum();
 
-    println!("Part two: {:?}", sum_of_middle);
+    sum_of_middle
 }
 
-fn main() {
-    let lines: Vec<&str> = include_str!("../input/05.in").lines().collect();
-
-    // Find the index of the empty line
-    let empty_index = lines
-        .iter()
-        .position(|line| line.is_empty())
-        .expect("No empty line found!");
+fn parse_print_queue(input: &str) -> (Vec<Vec<i32>>, DiGraph<i32>) {
+    read_print_queue(input).unwrap_or_else(|err| panic!("{}", err))
+}
 
+fn read_print_queue(input: &str) -> Result<(Vec<Vec<i32>>, DiGraph<i32>), ParseError> {
+    // Page ordering rules and updates are separated by an empty line; either may be missing.
+    // Without rules the input starts with the empty line.
+    let sections = parse::sections(input);
+    let (rules, updates) = match sections[..] {
+        [] => (None, None),
+        [updates] if updates.first_line > 1 => (None, Some(updates)),
+        [rules] => (Some(rules), None),
+        [rules, updates] => (Some(rules), Some(updates)),
+        [_, _, extra, ..] => {
+            let message = "unexpected third section after the updates".to_string();
+            return Err(ParseError { line: extra.first_line, column: 1, message });
+        }
+    };
 
     // 47|53 means that if an update includes both page number 47 and page number 53,
     // then page number 47 must be printed at some point before page number 53.
-    let print_constraints: Vec<(i32, i32)> = lines[..empty_index]
-        .iter()
-        .map(|line| {
-            let mut parts = line.split('|').map(|s| s.parse::<i32>().unwrap());
-            (parts.next().unwrap(), parts.next().unwrap())
-        })
-        .collect();
+    let mut print_constraints: Vec<(i32, i32)> = Vec::new();
+    if let Some(section) = rules {
+        for (i, line) in section.lines().enumerate() {
+            let line_number = section.first_line + i;
+            let pages: Vec<i32> = parse::ints(line).map_err(|err| err.below(line_number))?;
+            match pages[..] {
+                [before, after] => print_constraints.push((before, after)),
+                _ => {
+                    let message = format!("expected a rule `before|after`, found {} pages", pages.len());
+                    return Err(ParseError { line: line_number, column: 1, message });
+                }
+            }
+        }
+    }
 
-    let print_orders: Vec<Vec<i32>> = lines[empty_index+1..]
-        .iter()
-        .map(
-            |line| line
-                .split(',')
-                .map(|s| s.parse::<i32>().unwrap())
-                .collect()
-        )
-        .collect();
+    let mut print_orders: Vec<Vec<i32>> = Vec::new();
+    if let Some(section) = updates {
+        for (i, line) in section.lines().enumerate() {
+            print_orders.push(parse::ints(line).map_err(|err| err.below(section.first_line + i))?);
+        }
+    }
+
+    Ok((print_orders, DiGraph::from_edges(print_constraints)))
+}
+
+/// How far the rules of [`generate_with`] stray from one complete order of the pages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleConflicts {
+    /// Fraction of the rules left out, so that some pairs of pages have no rule between them.
+    pub missing: f64,
+    /// Rules added against the order, each between two pages of the same update, so that
+    /// the update cannot be ordered and part two fails.
+    pub back_edges: usize,
+}
 
-    let mut page_number_preds: HashMap<i32, Vec<i32>> = HashMap::new();
-    for (page_number, page_number_pred) in print_constraints {
-        page_number_preds
-            .entry(page_number)
-            .or_insert_with(Vec::new)
-            .push(page_number_pred);
+impl Default for RuleConflicts {
+    /// A tenth of the rules missing and no back edges, so every input can be solved.
+    fn default() -> Self {
+        RuleConflicts { missing: 0.1, back_edges: 0 }
     }
+}
+
+/// [`generate_with`] with the default [`RuleConflicts`].
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    generate_with(rng, size, RuleConflicts::default())
+}
 
-    part_one(print_orders.clone(), page_number_preds.clone());
-    part_two(print_orders.clone(), page_number_preds.clone());
+/// Rules that order `size` pages (at least 3, at most 89) like the real input, apart from
+/// the `conflicts`, and `size` updates of an odd number of pages. Half of the updates are
+/// in the order of the pages; the other half have one to three pairs of different pages
+/// swapped, which puts them out of order unless the swaps undo each other or only touch
+/// pages whose rule is missing.
+pub fn generate_with(rng: &mut Rng, size: usize, conflicts: RuleConflicts) -> String {
+    let mut pages: Vec<i64> = (10..=99).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(size.clamp(3, 89));
+
+    // `pages` is the order every rule agrees with, back edges aside
+    let mut rules: Vec<(i64, i64)> = Vec::new();
+    for (i, &before) in pages.iter().enumerate() {
+        for &after in &pages[i + 1..] {
+            if !rng.chance(conflicts.missing) {
+                rules.push((before, after));
+            }
+        }
+    }
+
+    let mut updates: Vec<Vec<i64>> = Vec::new();
+    for _ in 0..size {
+        // An odd number of pages, from 3 to 23
+        let len = 2 * rng.range(1, (pages.len().min(23) as i64 - 1) / 2) as usize + 1;
+        let mut positions: Vec<usize> = (0..pages.len()).collect();
+        rng.shuffle(&mut positions);
+        positions.truncate(len);
+        positions.sort_unstable();
+        updates.push(positions.iter().map(|&i| pages[i]).collect());
+    }
+
+    // Back edges join two pages of an update that also have the rule in order, so the update
+    // breaks one of the two and the rules between its pages form a cycle
+    for _ in 0..conflicts.back_edges.min(updates.len()) {
+        let update = rng.choose(&updates);
+        let i = rng.index(update.len() - 1);
+        let (before, after) = (update[i], update[i + 1 + rng.index(update.len() - i - 1)]);
+        if !rules.contains(&(before, after)) {
+            rules.push((before, after));
+        }
+        rules.push((after, before));
+    }
+    rng.shuffle(&mut rules);
+
+    for update in &mut updates {
+        if rng.chance(0.5) {
+            for _ in 0..rng.range(1, 3) {
+                let i = rng.index(update.len());
+                let j = (i + 1 + rng.index(update.len() - 1)) % update.len();
+                update.swap(i, j);
+            }
+        }
+    }
+
+    // The blank line is there even without updates
+    let mut input: String = rules.iter().map(|(before, after)| format!("{}|{}\n", before, after)).collect();
+    input.push('\n');
+    for update in updates {
+        input += &update.iter().map(|page| page.to_string()).collect::<Vec<_>>().join(",");
+        input.push('\n');
+    }
+    input
 }
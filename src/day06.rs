@@ -1,7 +1,17 @@
 // --- Day 6: Guard Gallivant ---
 
-use std::collections::HashSet;
 use std::convert::TryInto;
+use std::io;
+use std::path::Path;
+
+use crate::bitgrid::{BitGrid, BitGrid3};
+use crate::days::Snapshot;
+use crate::image::{Image, Rgb};
+use crate::random::Rng;
+use crate::recorder::Recorder;
+use crate::render::{Axis, Color, Frame};
+use crate::simulation::{Debugger, Simulation};
+use crate::viewer::{DebuggerViewer, Viewer};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 enum MazeField {
@@ -25,6 +35,16 @@ impl MazeField {
         }
     }
 
+    /// Layer of the guard direction in a `BitGrid3` of visited states.
+    fn direction_index(&self) -> usize {
+        match self {
+            MazeField::GuardUp => 0,
+            MazeField::GuardRight => 1,
+            MazeField::GuardDown => 2,
+            MazeField::GuardLeft => 3,
+            _ => unreachable!("not a guard field: {:?}", self),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq)]
@@ -35,22 +55,22 @@ struct GuardPosition {
 
 impl GuardPosition {
     fn empty() -> GuardPosition {
-        return GuardPosition { x: i32::MAX, y: i32::MAX };
+        GuardPosition { x: i32::MAX, y: i32::MAX }
     }
 }
 
 fn is_guard_maze_field(field: MazeField) -> bool {
-    return field == MazeField::GuardDown || field == MazeField::GuardUp || field == MazeField::GuardLeft || field == MazeField::GuardRight;
+    field == MazeField::GuardDown || field == MazeField::GuardUp || field == MazeField::GuardLeft || field == MazeField::GuardRight
 }
 
 fn turn_right(field: &MazeField) -> MazeField {
-    return match field {
+    match field {
         MazeField::GuardLeft => MazeField::GuardUp,
         MazeField::GuardUp => MazeField::GuardRight,
         MazeField::GuardRight => MazeField::GuardDown,
         MazeField::GuardDown => MazeField::GuardLeft,
         _ => todo!()
-    };
+    }
 }
 
 fn get_next_position(guard_direction: &MazeField, guard_position: &GuardPosition) -> GuardPosition {
@@ -63,62 +83,56 @@ fn get_next_position(guard_direction: &MazeField, guard_position: &GuardPosition
         MazeField::GuardRight => { x_diff = 1;},
         _ => todo!()
     }
-    return GuardPosition {x: guard_position.x + x_diff, y: guard_position.y + y_diff}
+    GuardPosition {x: guard_position.x + x_diff, y: guard_position.y + y_diff}
 }
 
-fn print_maze(maze: &Vec<Vec<MazeField>>, guard_visited_position_fields: &HashSet<(GuardPosition, MazeField)>, guard_position: &GuardPosition, guard_direction: &MazeField) {
-    let guard_visited_fields: HashSet<GuardPosition> =
-        guard_visited_position_fields
-            .iter()
-            .map(|(position, _)| position.clone())
-            .collect();
-
-    for (y, line) in maze.iter().enumerate() {
-        for (x, field) in line.iter().enumerate() {
-            // print maze field where the guard is currently located
-            // print maze field which has been visited by the guard
-            if guard_visited_fields.contains(&GuardPosition { x: x.try_into().unwrap(), y: y.try_into().unwrap() }) {
-                // print | if the MazeField is visited up or down only
-                // print - if the MazeField is visited left or right only
-                // print + if the MazeField is visited in both directions
-                let visited_fields = guard_visited_position_fields.iter().filter(|(position, _)| position.x == x.try_into().unwrap() && position.y == y.try_into().unwrap());
-
-                let mut visited_up = false;
-                let mut visited_down = false;
-                let mut visited_left = false;
-                let mut visited_right = false;
-                for (_, field) in visited_fields {
-                    match field {
-                        MazeField::GuardUp => visited_up = true,
-                        MazeField::GuardDown => visited_down = true,
-                        MazeField::GuardLeft => visited_left = true,
-                        MazeField::GuardRight => visited_right = true,
-                        _ => todo!()
-                    }
-                }
-
-                if (visited_up || visited_down) && (visited_left || visited_right) {
-                    print!("+");
-                } else if visited_up || visited_down {
-                    print!("|");
-                } else if visited_left || visited_right {
-                    print!("-");
-                }
-
-            } else {
-                print!("{}", field.as_str());
-            }
-        }
-        println!("")
+fn maze_frame(walk: &GuardWalk) -> Frame {
+    // draw | if the MazeField is visited up or down only
+    // draw - if the MazeField is visited left or right only
+    // draw + if the MazeField is visited in both directions
+    let path = walk.state.visited.iter().map(|(y, x, direction)| {
+        let is_vertical = direction == MazeField::GuardUp.direction_index()
+            || direction == MazeField::GuardDown.direction_index();
+        ((y, x), if is_vertical { Axis::Vertical } else { Axis::Horizontal })
+    });
+
+    let mut frame = Frame::from_grid(&walk.maze, |field| field.as_str().chars().next().unwrap());
+    frame.overlay_path(path, Some(Color::Yellow));
+
+    // draw maze field where the guard is currently located
+    if let Some(position) = walk.position() {
+        let guard_glyph = walk.state.direction.as_str().chars().next().unwrap();
+        frame.label([(position, guard_glyph)], Some(Color::BrightRed));
     }
-    println!("")
+    frame
+}
 
+fn maze_image(walk: &GuardWalk) -> Image {
+    let palette = |field: &MazeField| match field {
+        MazeField::Empty => Rgb(24, 24, 24),
+        MazeField::Obstacle => Rgb::GREY,
+        _ => Rgb::RED,
+    };
+    let mut image = Image::from_grid(&walk.maze, &palette);
+    image.tint(walk.state.visited.flatten().iter(), Rgb::YELLOW, 0.8);
+    image.overlay(walk.position(), Rgb::RED);
+    image
+}
+
+/// Saves the maze after the guard's walk, with every field they stepped on tinted.
+pub fn save_maze_png(input: &str, path: &Path, scale: u32) -> io::Result<()> {
+    let (guard_position, maze) = create_maze(input);
+    let mut walk = GuardWalk::new(guard_position, maze);
+    while !walk.is_done() {
+        walk.step();
+    }
+    maze_image(&walk).write_png(path, scale)
 }
 
-fn create_maze(lines: Vec<&str>) -> (GuardPosition, Vec<Vec<MazeField>>) {
+fn create_maze(input: &str) -> (GuardPosition, Vec<Vec<MazeField>>) {
     let mut maze: Vec<Vec<MazeField>> = Vec::new();
     let mut guard_position: GuardPosition = GuardPosition::empty();
-    for (i, &line) in lines.iter().enumerate() {
+    for (i, line) in input.lines().enumerate() {
         let mut maze_line: Vec<MazeField> = Vec::new();
         for (j, maze_field_str) in line.chars().enumerate() {
             let maze_field = match maze_field_str {
@@ -137,139 +151,200 @@ fn create_maze(lines: Vec<&str>) -> (GuardPosition, Vec<Vec<MazeField>>) {
         }
         maze.push(maze_line.clone())
     }
-    return (guard_position, maze);
+    (guard_position, maze)
 }
 
-fn move_guard_to_next_position(
-    guard_position: &mut GuardPosition,
-    guard_direction: &mut MazeField,
-    guard_visited_fields: &mut HashSet<GuardPosition>,
-    maze: &mut Vec<Vec<MazeField>>,
-    maze_width: usize,
-    maze_height: usize,
-) -> bool {
-    let next_position = get_next_position(&guard_direction, &guard_position);
-
-    if (next_position.x < 0 || next_position.x >= maze_width.try_into().unwrap()) ||
-        (next_position.y < 0 || next_position.y >= maze_height.try_into().unwrap()) {
-        *guard_position = GuardPosition{y: next_position.y, x: next_position.x };
-        return false;
-    }
+/// Everything that changes while the guard walks; the maze itself stays untouched.
+#[derive(Debug, Clone)]
+struct GuardState {
+    position: GuardPosition,
+    direction: MazeField,
+    /// Every field the guard has been on, one layer per direction
+    visited: BitGrid3,
+    /// Being in the same place facing the same way again means the guard is stuck in a loop
+    is_looping: bool,
+}
 
-    let guard_next_x: usize = next_position.x.try_into().unwrap();
-    let guard_next_y: usize = next_position.y.try_into().unwrap();
-    let next_field = &maze[guard_next_y][guard_next_x];
+/// The guard walking through the maze until they leave it or get stuck in a loop.
+struct GuardWalk {
+    /// The maze without the guard
+    maze: Vec<Vec<MazeField>>,
+    state: GuardState,
+}
 
-    let guard_x: usize = guard_position.x.try_into().unwrap();
-    let guard_y: usize = guard_position.y.try_into().unwrap();
-    // CASE 1: in front of the obstacle -> turn right
-    if *next_field == MazeField::Obstacle {
-        *guard_direction = turn_right(guard_direction);
-        maze[guard_y][guard_x] = guard_direction.clone();
-        return true;
-    }
-    // CASE 2: not in front of the obstacle -> walk further
-    else if *next_field == MazeField::Empty {
+impl GuardWalk {
+    fn new(guard_position: GuardPosition, mut maze: Vec<Vec<MazeField>>) -> GuardWalk {
+        let guard_x: usize = guard_position.x.try_into().unwrap();
+        let guard_y: usize = guard_position.y.try_into().unwrap();
+        let guard_direction = maze[guard_y][guard_x].clone();
         maze[guard_y][guard_x] = MazeField::Empty;
-        guard_position.x = next_position.x;
-        guard_position.y = next_position.y;
-        maze[guard_next_y][guard_next_x] = guard_direction.clone();
-        guard_visited_fields.insert(GuardPosition{y: next_position.y, x: next_position.x });
-        // print_maze(&maze, &guard_visited_fields);
-        return true;
-    } else {
-        // raise exception - unhandled case
-        panic!("Unhandled case");
+
+        let mut visited = BitGrid3::new(maze.len(), maze[0].len(), 4);
+        visited.insert(guard_y, guard_x, guard_direction.direction_index());
+        let state = GuardState { position: guard_position, direction: guard_direction, visited, is_looping: false };
+        GuardWalk { maze, state }
+    }
+
+    /// Puts the guard back where they started with nothing visited, keeping the maze and the
+    /// space for the visited fields, e.g. to walk again after placing an obstacle.
+    fn restart(&mut self, guard_position: &GuardPosition, guard_direction: &MazeField) {
+        let guard_x: usize = guard_position.x.try_into().unwrap();
+        let guard_y: usize = guard_position.y.try_into().unwrap();
+        self.state.visited.clear();
+        self.state.visited.insert(guard_y, guard_x, guard_direction.direction_index());
+        self.state.position = guard_position.clone();
+        self.state.direction = guard_direction.clone();
+        self.state.is_looping = false;
     }
 }
 
-fn part_one(mut guard_position: GuardPosition, mut maze: Vec<Vec<MazeField>>) -> HashSet<GuardPosition> {
-    let maze_height = maze.len();
-    let maze_width = maze[0].len();
-    let mut distinct_positions_count = 0;
-    let guard_x: usize = guard_position.x.try_into().unwrap();
-    let guard_y: usize = guard_position.y.try_into().unwrap();
-    let mut guard_direction = maze[guard_y][guard_x].clone();
-    let mut guard_visited_fields: HashSet<GuardPosition> = HashSet::new();
-    loop {
-        let is_guard_in_bounds = move_guard_to_next_position(
-            &mut guard_position,
-            &mut guard_direction,
-            &mut guard_visited_fields,
-            &mut maze,
-            maze_width,
-            maze_height
-        );
-        if !is_guard_in_bounds {
-            break;
+impl Simulation for GuardWalk {
+    type State = GuardState;
+
+    fn state(&self) -> &GuardState {
+        &self.state
+    }
+
+    fn set_state(&mut self, state: GuardState) {
+        self.state = state;
+    }
+
+    fn step(&mut self) {
+        let next_position = get_next_position(&self.state.direction, &self.state.position);
+        let next_field = usize::try_from(next_position.y)
+            .ok()
+            .zip(usize::try_from(next_position.x).ok())
+            .and_then(|(y, x)| self.maze.get(y)?.get(x));
+
+        match next_field {
+            // CASE 1: in front of the obstacle -> turn right
+            Some(MazeField::Obstacle) => self.state.direction = turn_right(&self.state.direction),
+            // CASE 2: not in front of the obstacle -> walk further, possibly out of the maze
+            _ => self.state.position = next_position,
         }
+
+        if let Some((y, x)) = self.position() {
+            let is_new_state = self.state.visited.insert(y, x, self.state.direction.direction_index());
+            self.state.is_looping = !is_new_state;
+        }
+    }
+
+    fn is_done(&self) -> bool {
+        self.position().is_none() || self.state.is_looping
     }
 
-    println!("Part one {:?}", guard_visited_fields.len());
+    /// `(row, col)` of the guard while they are in the maze.
+    fn position(&self) -> Option<(usize, usize)> {
+        let x = usize::try_from(self.state.position.x).ok()?;
+        let y = usize::try_from(self.state.position.y).ok()?;
+        (y < self.maze.len() && x < self.maze[0].len()).then_some((y, x))
+    }
+}
+
+/// Walks the guard until they leave the maze and returns every field they stepped on,
+/// handing the maze to `recorder` before the first step and after every step.
+fn walk_guard(guard_position: GuardPosition, maze: Vec<Vec<MazeField>>, recorder: &mut Recorder) -> io::Result<BitGrid> {
+    let mut walk = GuardWalk::new(guard_position, maze);
+    recorder.record(|| maze_image(&walk))?;
+    while !walk.is_done() {
+        walk.step();
+        recorder.record(|| maze_image(&walk))?;
+    }
+    Ok(walk.state.visited.flatten())
+}
+
+pub fn part_one(input: &str) -> usize {
+    let (guard_position, maze) = create_maze(input);
+    let guard_visited_fields = walk_guard(guard_position, maze, &mut Recorder::disabled()).unwrap();
+    guard_visited_fields.count_ones()
+}
 
-    return guard_visited_fields;
+/// Records the guard's walk of part one.
+pub fn record(input: &str, recorder: &mut Recorder) -> io::Result<()> {
+    let (guard_position, maze) = create_maze(input);
+    walk_guard(guard_position, maze, recorder)?;
+    Ok(())
 }
 
-fn part_two(guard_position: GuardPosition, mut maze: Vec<Vec<MazeField>>, guard_visited_fields: HashSet<GuardPosition>) {
-    let maze_height = maze.len();
-    let maze_width = maze[0].len();
-    let mut distinct_positions_count = 0;
+pub fn part_two(input: &str) -> usize {
+    let (guard_position, maze) = create_maze(input);
+    let guard_visited_fields = walk_guard(guard_position.clone(), maze.clone(), &mut Recorder::disabled()).unwrap();
     let guard_x: usize = guard_position.x.try_into().unwrap();
     let guard_y: usize = guard_position.y.try_into().unwrap();
-    let mut guard_direction = maze[guard_y][guard_x].clone();
     let mut time_paradox_obstruction_count = 0;
 
-    for guard_path in guard_visited_fields {
-        let path_x: usize = guard_path.x.try_into().unwrap();
-        let path_y: usize = guard_path.y.try_into().unwrap();
+    // One walk for every candidate obstacle: placed in the maze and taken out again afterwards
+    let mut walk = GuardWalk::new(guard_position.clone(), maze);
+    let guard_direction = walk.state.direction.clone();
+
+    for (path_y, path_x) in guard_visited_fields.iter() {
         // skip the guard position
         if path_x == guard_x && path_y == guard_y {
             continue;
         }
 
-        // println!("placing obstacle at {:?}", guard_path);
-
-        let mut tmp_maze = maze.clone();
-        let mut tmp_guard_position = guard_position.clone();
-        let mut tmp_guard_direction = guard_direction.clone();
-        let mut tmp_guard_visited_fields: HashSet<GuardPosition> = HashSet::new();
-        let mut tmp_guard_visited_fields_directions: HashSet<(GuardPosition, MazeField)> = HashSet::new();
-
-        // print_maze(&tmp_maze, &tmp_guard_visited_fields_directions, &tmp_guard_position, &tmp_guard_direction);
-        tmp_maze[path_y][path_x] = MazeField::Obstacle;
-        // print_maze(&tmp_maze, &tmp_guard_visited_fields_directions, &tmp_guard_position, &tmp_guard_direction);
-        tmp_guard_visited_fields_directions.insert((tmp_guard_position.clone(), tmp_guard_direction.clone()));
-
-        loop {
-            let is_guard_in_bounds = move_guard_to_next_position(
-                &mut tmp_guard_position,
-                &mut tmp_guard_direction,
-                &mut tmp_guard_visited_fields,
-                &mut tmp_maze,
-                maze_width,
-                maze_height
-            );
-            // print_maze(&tmp_maze, &tmp_guard_visited_fields_directions, &tmp_guard_position, &tmp_guard_direction);
-            let current_guard_field_direction: (GuardPosition, MazeField)  = (tmp_guard_position.clone(), tmp_guard_direction.clone());
-            if tmp_guard_visited_fields_directions.contains(&current_guard_field_direction) {
-                time_paradox_obstruction_count += 1;
-                break;
-            }
-            tmp_guard_visited_fields_directions.insert(current_guard_field_direction);
-            if !is_guard_in_bounds {
-                break;
-            }
+        walk.maze[path_y][path_x] = MazeField::Obstacle;
+        walk.restart(&guard_position, &guard_direction);
+        while !walk.is_done() {
+            walk.step();
+        }
+        if walk.state.is_looping {
+            time_paradox_obstruction_count += 1;
         }
+        walk.maze[path_y][path_x] = MazeField::Empty;
     }
 
-    println!("Part two {:?}", time_paradox_obstruction_count);
+    time_paradox_obstruction_count
+}
+
+fn walk_snapshot(walk: &GuardWalk, step: usize) -> Snapshot {
+    let GuardState { position, direction, visited, .. } = walk.state();
+    let place = match walk.position() {
+        Some(_) => format!("guard at ({}, {}) facing {}", position.x, position.y, direction.as_str()),
+        None => "guard left the maze".to_string(),
+    };
+    let status = format!("step {}: {}, {} distinct positions", step, place, visited.flatten().count_ones());
+    Snapshot { frame: maze_frame(walk), status, focus: walk.position() }
 }
 
-fn main() {
-    let lines: Vec<&str> = include_str!("../input/06.in").lines().collect();
+/// Steps through the guard walk of part one, one move at a time, with a breakpoint on the
+/// guard running into an obstacle.
+pub fn view(input: &str) -> Option<Box<dyn Viewer>> {
+    let (guard_position, maze) = create_maze(input);
+    let walk = GuardWalk::new(guard_position, maze);
+    let maze = walk.maze.clone();
+    let faces_obstacle = move |state: &GuardState| {
+        let ahead = get_next_position(&state.direction, &state.position);
+        let field = usize::try_from(ahead.y).ok().zip(usize::try_from(ahead.x).ok()).and_then(|(y, x)| maze.get(y)?.get(x));
+        field == Some(&MazeField::Obstacle)
+    };
+    let viewer = DebuggerViewer::new(Debugger::new(walk), Box::new(walk_snapshot))
+        .with_condition("guard is about to turn", faces_obstacle);
+    Some(Box::new(viewer))
+}
 
-    let (maze, guard_position) = create_maze(lines.clone());
+/// A `size x size` maze with an obstacle on about one field in twelve and the guard facing up
+/// somewhere inside. Mazes in which the guard would walk in a loop are drawn again, so the
+/// guard always finds the exit.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    loop {
+        let mut maze: Vec<Vec<MazeField>> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| if rng.chance(0.08) { MazeField::Obstacle } else { MazeField::Empty })
+                    .collect()
+            })
+            .collect();
+        let guard_position = GuardPosition { x: rng.index(size) as i32, y: rng.index(size) as i32 };
+        maze[guard_position.y as usize][guard_position.x as usize] = MazeField::GuardUp;
 
-    let guard_visited_fields = part_one(maze.clone(), guard_position.clone());
-    part_two(maze.clone(), guard_position.clone(), guard_visited_fields.clone());
+        let mut walk = GuardWalk::new(guard_position, maze.clone());
+        while !walk.is_done() {
+            walk.step();
+        }
+        if !walk.state.is_looping {
+            return maze.iter().map(|line| line.iter().map(MazeField::as_str).collect::<String>() + "\n").collect();
+        }
+    }
 }
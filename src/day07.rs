@@ -1,3 +1,7 @@
+use crate::digits;
+use crate::parse::{self, ParseError};
+use crate::random::Rng;
+
 // --- Day 7: Bridge Repair ---
 
 #[derive(Clone)]
@@ -17,7 +21,8 @@ impl Equation {
                 equation_res = match operator {
                     "+" => equation_res + b,
                     "*" => equation_res * b,
-                    "|" => format!("{}{}", equation_res, b).parse::<i64>().unwrap(),
+                    // An overflowing concatenation can never match the result
+                    "|" => digits::concat(equation_res, b).unwrap_or(i64::MAX),
                     _ => todo!()
                 };
                 if equation_res > self.result {
@@ -28,74 +33,93 @@ impl Equation {
                 return true
             }
         }
-        return false
+        false
     }
 
-    fn generate_all_equations(&mut self, operators: &Vec<&str>) -> Vec<Vec<String>> {
+    fn generate_all_equations(&mut self, operators: &[&str]) -> Vec<Vec<String>> {
         let mut equations: Vec<Vec<String>> = vec![vec![self.sequence[0].to_string()]];
         for i in 1..self.sequence.len() {
             let mut equations_updated: Vec<Vec<String>> = Vec::new();
-            for j in 0..equations.len() {
+            for equation in &equations {
                 for &operator in operators {
-                    let mut tmp_str_vec1 = equations[j].clone();
+                    let mut tmp_str_vec1 = equation.clone();
                     tmp_str_vec1.push(operator.to_string());
                     equations_updated.push(tmp_str_vec1);
                 }
             }
             equations = equations_updated;
-            for j in 0..equations.len() {
-                equations[j].push(self.sequence[i].to_string())
-
+            for equation in &mut equations {
+                equation.push(self.sequence[i].to_string())
             }
         }
-        return equations;
+        equations
     }
 }
 
-fn part_one(equations: Vec<Equation>) {
+pub fn part_one(input: &str) -> i64 {
+    let equations = parse_equations(input);
     let mut result_sum = 0;
     for mut equation in equations {
         if equation.is_sound(vec!["+", "*"]) {
             result_sum += equation.result;
         }
     }
-    println!("Part one: {:?}", result_sum)
+    result_sum
 }
 
-fn part_two(equations: Vec<Equation>) {
+pub fn part_two(input: &str) -> i64 {
+    let equations = parse_equations(input);
     let mut result_sum = 0;
     for mut equation in equations {
         if equation.is_sound(vec!["+", "*", "|"]) {
             result_sum += equation.result;
         }
     }
-    println!("Part rw0tw0: {:?}", result_sum)
+    result_sum
 }
 
+fn parse_equations(input: &str) -> Vec<Equation> {
+    read_equations(input).unwrap_or_else(|err| panic!("{}", err))
+}
 
-fn main() {
-    let lines: Vec<Equation> = include_str!("../input/07.in")
-        .lines()
-        .map(|x| {
-            let mut equation_str = x.split(':');
-            let result: i64 = equation_str
-                .next()
-                .clone()
-                .unwrap()
-                .parse::<i64>()
-                .unwrap();
-            let sequence: Vec<i64> = equation_str
-                .next()
-                .clone()
-                .unwrap()
-                .split_whitespace()
-                .map(|x| x.parse::<i64>().unwrap())
-                .collect();
-
-            Equation { result, sequence }
-        }).collect();
-
+fn read_equations(input: &str) -> Result<Vec<Equation>, ParseError> {
+    // Each line is `result: a b c ...`, read as a whole so errors point at the right column
+    let mut equations = Vec::new();
+    for (i, line) in parse::lines(input).enumerate().filter(|(_, line)| !line.is_empty()) {
+        let numbers: Vec<i64> = parse::ints(line).map_err(|err| err.below(i + 1))?;
+        match (line.contains(':'), &numbers[..]) {
+            (true, [result, sequence @ ..]) if !sequence.is_empty() => {
+                equations.push(Equation { result: *result, sequence: sequence.to_vec() });
+            }
+            _ => {
+                let message = "expected `result: a b ...`".to_string();
+                return Err(ParseError { line: i + 1, column: 1, message });
+            }
+        }
+    }
+    Ok(equations)
+}
 
-    part_one(lines.clone());
-    part_two(lines.clone());
+/// `size` equations of 2 to 8 numbers below 100. Most results combine the numbers with
+/// random `+`, `*` and `||` operators; the others are off by one and usually cannot be solved.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut lines = String::new();
+    for _ in 0..size {
+        let sequence: Vec<i64> = (0..rng.range(2, 8)).map(|_| rng.range(1, 99)).collect();
+        let mut result = sequence[0];
+        for &b in &sequence[1..] {
+            // At most two more digits per number, so the result fits even with eight numbers
+            result = match rng.below(3) {
+                0 => result + b,
+                1 => result * b,
+                _ => digits::concat(result, b).unwrap(),
+            };
+        }
+        if rng.chance(0.3) {
+            result += 1;
+        }
+        let sequence: Vec<String> = sequence.iter().map(|number| number.to_string()).collect();
+        lines += &format!("{}: {}\n", result, sequence.join(" "));
+    }
+    lines
 }
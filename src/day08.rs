@@ -1,6 +1,13 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
+use std::io;
+use std::path::Path;
 use itertools::Itertools;
+use crate::days::Snapshot;
+use crate::image::{Image, Rgb};
+use crate::math;
+use crate::random::Rng;
+use crate::render::{Color, Frame};
 
 #[derive(Debug, Eq, Hash, PartialEq)]
 struct Signal {
@@ -13,39 +20,52 @@ fn is_position_in_bounds(x: i32, y: i32, width: i32, height: i32) -> bool {
     x >= 0 && x < width && y >= 0 && y < height
 }
 
-fn print_map(signals: &HashMap<String, Vec<Signal>>, antinodes: &HashSet<(i32, i32)>, width: i32, height: i32) {
-    for i in 0..height {
-        for j in 0..width {
-            let mut printed = false;
-            for (_, value) in signals.iter() {
-                for signal in value {
-                    if signal.x == j && signal.y == i {
-                        print!("{}", signal.value);
-                        printed = true;
-                        break;
-                    }
-                }
-                if printed {
-                    break;
-                }
-            }
+fn map_frame(signals: &HashMap<String, Vec<Signal>>, antinodes: &HashSet<(i32, i32)>, width: i32, height: i32) -> Frame {
+    let in_bounds_antinodes: Vec<(usize, usize)> = antinodes
+        .iter()
+        .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
+        .map(|&(x, y)| (y as usize, x as usize))
+        .collect();
+
+    // Antennas are drawn over antinodes; antinodes stay visible as a highlighted background
+    let mut frame = Frame::filled(height as usize, width as usize, '.');
+    frame.label(in_bounds_antinodes.iter().map(|&position| (position, '#')), Some(Color::Red));
+    frame.label(
+        signals
+            .values()
+            .flatten()
+            .map(|signal| ((signal.y as usize, signal.x as usize), signal.value.chars().next().unwrap())),
+        Some(Color::Cyan),
+    );
+    frame.highlight(in_bounds_antinodes, Color::BrightBlack);
+    frame
+}
 
-            if !printed {
-                if antinodes.contains(&(j, i)) {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-        }
-        println!();
+/// Saves the map with the antinodes of part two in red and every frequency's antennas in their own colour.
+pub fn save_map_png(input: &str, path: &Path, scale: u32) -> io::Result<()> {
+    let (signals, width, height) = parse_signals(input);
+    let antinodes = find_resonant_antinodes(&signals, width, height);
+    let mut image = Image::filled(height as usize, width as usize, Rgb(24, 24, 24));
+    image.overlay(
+        antinodes
+            .iter()
+            .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
+            .map(|&(x, y)| (y as usize, x as usize)),
+        Rgb::RED,
+    );
+    // Antennas of one frequency share a colour, and stay visible on top of antinodes
+    for (frequency, value) in signals.iter() {
+        let color = Rgb::from_label(frequency.chars().next().unwrap() as usize);
+        image.overlay(value.iter().map(|signal| (signal.y as usize, signal.x as usize)), color);
     }
+    image.write_png(path, scale)
 }
 
-fn part_one(signals: &HashMap<String, Vec<Signal>>, width: i32, height: i32) {
+pub fn part_one(input: &str) -> usize {
+    let (signals, width, height) = parse_signals(input);
     let mut antinodes: HashSet<(i32, i32)> = HashSet::new();
 
-    for (_, value) in signals.iter() {
+    for value in signals.values() {
         if value.len() < 2 {
             continue;
         }
@@ -63,19 +83,17 @@ fn part_one(signals: &HashMap<String, Vec<Signal>>, width: i32, height: i32) {
         }
     }
 
-    println!(
-        "Part one: {}",
-        antinodes
-            .iter()
-            .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
-            .count()
-    );
+    antinodes
+        .iter()
+        .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
+        .count()
 }
 
-fn part_two(signals: &HashMap<String, Vec<Signal>>, width: i32, height: i32) {
+/// Antinodes with resonant harmonics: every grid position in line with at least two antennas.
+fn find_resonant_antinodes(signals: &HashMap<String, Vec<Signal>>, width: i32, height: i32) -> HashSet<(i32, i32)> {
     let mut antinodes: HashSet<(i32, i32)> = HashSet::new();
 
-    for (_, value) in signals.iter() {
+    for value in signals.values() {
         if value.len() < 2 {
             continue;
         }
@@ -87,39 +105,57 @@ fn part_two(signals: &HashMap<String, Vec<Signal>>, width: i32, height: i32) {
             .collect();
 
         for (a, b) in pairs {
-            let norm_vector = (a.x - b.x, a.y - b.y);
-            let mut antinode_a = (a.x + norm_vector.0, a.y + norm_vector.1);
-            let mut antinode_b = (b.x - norm_vector.0, b.y - norm_vector.1);
+            // Reduce the difference by its gcd so that every grid position on the line
+            // through both antennas is visited, including the ones between them
+            let gcd = math::gcd((a.x - b.x) as i64, (a.y - b.y) as i64) as i32;
+            let norm_vector = ((a.x - b.x) / gcd, (a.y - b.y) / gcd);
+            let mut antinode_a = (a.x, a.y);
+            let mut antinode_b = (a.x - norm_vector.0, a.y - norm_vector.1);
 
             while is_position_in_bounds(antinode_a.0, antinode_a.1, width, height) {
-                antinodes.insert(antinode_a.clone());
+                antinodes.insert(antinode_a);
                 antinode_a = (antinode_a.0 + norm_vector.0, antinode_a.1 + norm_vector.1);
             }
 
             while is_position_in_bounds(antinode_b.0, antinode_b.1, width, height) {
-                antinodes.insert(antinode_b.clone());
+                antinodes.insert(antinode_b);
                 antinode_b = (antinode_b.0 - norm_vector.0, antinode_b.1 - norm_vector.1);
             }
         }
     }
 
-    for (_, value) in signals.iter() {
+    for value in signals.values() {
         for signal in value {
             antinodes.insert((signal.x, signal.y));
         }
     }
 
-    println!(
-        "Part two: {}",
-        antinodes
-            .iter()
-            .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
-            .count()
-    );
+    antinodes
+}
+
+pub fn part_two(input: &str) -> usize {
+    let (signals, width, height) = parse_signals(input);
+    let antinodes = find_resonant_antinodes(&signals, width, height);
+
+    antinodes
+        .iter()
+        .filter(|(x, y)| is_position_in_bounds(*x, *y, width, height))
+        .count()
 }
 
-fn main() {
-    let lines: Vec<&str> = include_str!("../input/08.in").lines().collect();
+/// The antenna map with the antinodes of part two.
+pub fn snapshot(input: &str, step: usize) -> Option<Snapshot> {
+    if step > 0 {
+        return None;
+    }
+    let (signals, width, height) = parse_signals(input);
+    let antinodes = find_resonant_antinodes(&signals, width, height);
+    let status = format!("{} antinodes, {} frequencies", antinodes.len(), signals.len());
+    Some(Snapshot { frame: map_frame(&signals, &antinodes, width, height), status, focus: None })
+}
+
+fn parse_signals(input: &str) -> (HashMap<String, Vec<Signal>>, i32, i32) {
+    let lines: Vec<&str> = input.lines().collect();
     let mut signals: HashMap<String, Vec<Signal>> = HashMap::new();
 
     for (y, line) in lines.iter().enumerate() {
@@ -129,13 +165,28 @@ fn main() {
                 y: y.try_into().unwrap(),
                 value: value.to_string(),
             };
-            signals.entry(value.to_string()).or_insert(Vec::new()).push(signal);
+            signals.entry(value.to_string()).or_default().push(signal);
         }
     }
 
     let width: i32 = lines[0].split_whitespace().count().try_into().unwrap();
     let height: i32 = lines.len().try_into().unwrap();
 
-    part_one(&signals, width, height);
-    part_two(&signals, width, height);
-}
\ No newline at end of file
+    (signals, width, height)
+}
+
+/// A `size x size` map with one frequency per 8 rows (letters and digits), each with 2 to 4 antennas.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    const FREQUENCIES: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    let size = size.max(1);
+    let mut map = vec![vec![b'.'; size]; size];
+    for &frequency in FREQUENCIES.iter().take(1 + size / 8) {
+        for _ in 0..rng.range(2, 4) {
+            let (row, col) = (rng.index(size), rng.index(size));
+            if map[row][col] == b'.' {
+                map[row][col] = frequency;
+            }
+        }
+    }
+    map.into_iter().map(|line| String::from_utf8(line).unwrap() + "\n").collect()
+}
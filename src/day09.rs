@@ -1,122 +1,294 @@
+use std::io;
+
+use crate::days::Snapshot;
+use crate::image::{Image, Rgb};
+use crate::parse;
+use crate::random::Rng;
+use crate::recorder::Recorder;
+use crate::render::{Color, Frame};
+use crate::simulation::{Debugger, Simulation};
+use crate::spans::{FileSpan, FreeSpans, Span};
+use crate::viewer::{DebuggerViewer, Viewer};
+
 // --- Day 9: Disk Fragmenter ---
 
-fn part_one(mut memory_map: Vec<String>) {
-    let mut first_free_idx = memory_map.iter().position(|c| c == ".").unwrap_or(memory_map.len());
-    let mut last_full_idx = memory_map.iter().rposition(|c| c != ".").unwrap_or(0);
+pub fn part_one(input: &str) -> usize {
+    // Move file blocks one at a time from the end of the disk to the leftmost free block.
+    // Whole runs are moved at once: as many blocks as fit into the leftmost free span.
+    let (files, mut free_spans) = parse_disk(input);
+    let mut compacted: Vec<FileSpan> = Vec::with_capacity(files.len());
+    let mut remaining: Vec<FileSpan> = files;
 
-    while first_free_idx < last_full_idx {
-        // Swap the elements
-        memory_map.swap(first_free_idx, last_full_idx);
+    while let Some(mut file) = remaining.pop() {
+        while !file.span.is_empty() {
+            let Some(free) = free_spans.take_leftmost_fitting(1, file.span.start) else {
+                break;
+            };
+            let moved = free.len.min(file.span.len);
+            compacted.push(FileSpan { id: file.id, span: Span::new(free.start, moved) });
+            free_spans.insert(Span::new(free.start + moved, free.len - moved));
+            file.span.len -= moved;
+        }
 
-        // Find the next free and full indices
-        first_free_idx = memory_map.iter().position(|c| c == ".").unwrap_or(memory_map.len());
-        last_full_idx = memory_map.iter().rposition(|c| c != ".").unwrap_or(0);
+        if !file.span.is_empty() {
+            // No free space left of this file, so none left of any file before it either
+            compacted.push(file);
+            compacted.append(&mut remaining);
+        }
     }
 
-    // Calculate checksum
-    println!("Part one: {}", compute_checksum(memory_map));
+    compute_checksum(&compacted)
 }
 
-fn part_two(mut memory_map: Vec<String>) {
-    // Attempt to move whole files to the leftmost span of free space blocks that could fit the file
-    // Files are moved in decreasing order of file ID, starting with the highest
-    // If no span of free space to the left is large enough, the file does not move
+/// Blocks per row when drawing the disk.
+const DISK_IMAGE_WIDTH: usize = 200;
 
-    let num_of_files = memory_map
-        .iter()
-        .filter_map(|c| c.parse::<usize>().ok())
-        .max()
-        .unwrap();
-
-    for file_id in (0..=num_of_files).rev() {
-        // Find the starting index and size of the current file
-        if let Some(file_start_idx) = memory_map.iter().position(|c| c == &file_id.to_string()) {
-            let file_size = memory_map
-                .iter()
-                .skip(file_start_idx)
-                .take_while(|&c| c == &file_id.to_string())
-                .count();
-
-            // Search for the nearest free block to the left of the file
-            let mut first_free_idx = memory_map.iter().position(|c| c == ".").unwrap_or(memory_map.len());
-
-            while first_free_idx < file_start_idx {
-                let free_mem_size = memory_map
-                    .iter()
-                    .skip(first_free_idx)
-                    .take_while(|&c| c == ".")
-                    .count();
-
-                if free_mem_size >= file_size {
-                    // Move the file to the free memory (left side)
-                    memory_map.splice(
-                        first_free_idx..first_free_idx + file_size,
-                        vec![file_id.to_string(); file_size],
-                    );
-
-                    // Clear the original positions of the file
-                    memory_map
-                        .splice(file_start_idx..file_start_idx + file_size, vec![".".to_string(); file_size]);
-
-                    break;
-                }
-
-                // Move to the next free block of memory
-                first_free_idx += free_mem_size;
-                first_free_idx += memory_map
-                    .iter()
-                    .skip(first_free_idx)
-                    .take_while(|&c| c != ".")
-                    .count();
+/// Draws the disk as rows of blocks, every file in its own colour.
+fn disk_image(files: &[FileSpan], disk_size: usize) -> Image {
+    let mut image = Image::filled(disk_size.div_ceil(DISK_IMAGE_WIDTH), DISK_IMAGE_WIDTH, Rgb::BLACK);
+    for file in files {
+        let blocks = file.span.start..file.span.end();
+        image.overlay(blocks.map(|i| (i / DISK_IMAGE_WIDTH, i % DISK_IMAGE_WIDTH)), Rgb::from_label(file.id));
+    }
+    image
+}
+
+/// Draws the disk as text, `DISK_IMAGE_WIDTH` blocks per row: every file block shows
+/// the last base-36 digit of its ID in the file's colour, free blocks are `.`.
+fn disk_frame(files: &[FileSpan], disk_size: usize) -> Frame {
+    let mut frame = Frame::filled(disk_size.div_ceil(DISK_IMAGE_WIDTH), DISK_IMAGE_WIDTH, '.');
+    for file in files {
+        let glyph = char::from_digit((file.id % 36) as u32, 36).unwrap();
+        let blocks = file.span.start..file.span.end();
+        frame.label(blocks.map(|i| ((i / DISK_IMAGE_WIDTH, i % DISK_IMAGE_WIDTH), glyph)), Some(Color::from_label(file.id)));
+    }
+    frame
+}
+
+/// State of the whole-file compaction of part two.
+#[derive(Debug, Clone)]
+struct CompactionState {
+    files: Vec<FileSpan>,
+    free_spans: FreeSpans,
+    /// Files from index `next` on have been moved, or cannot move
+    next: usize,
+    last_moved: Option<usize>,
+}
+
+/// Part two as a simulation: every step moves one file.
+struct FileCompaction {
+    state: CompactionState,
+}
+
+impl FileCompaction {
+    fn new(files: &[FileSpan], free_spans: &FreeSpans) -> FileCompaction {
+        let state = CompactionState {
+            files: files.to_vec(),
+            free_spans: free_spans.clone(),
+            next: files.len(),
+            last_moved: None,
+        };
+        let mut compaction = FileCompaction { state };
+        compaction.skip_unmovable_files();
+        compaction
+    }
+
+    /// Skips the files that stay where they are, so that the next step moves a file.
+    fn skip_unmovable_files(&mut self) {
+        let state = &mut self.state;
+        while state.next > 0 {
+            let file = state.files[state.next - 1];
+            if state.free_spans.leftmost_fitting(file.span.len, file.span.start).is_some() {
+                break;
             }
+            state.next -= 1;
         }
     }
+}
+
+impl Simulation for FileCompaction {
+    type State = CompactionState;
+
+    fn state(&self) -> &CompactionState {
+        &self.state
+    }
+
+    fn set_state(&mut self, state: CompactionState) {
+        self.state = state;
+    }
+
+    fn step(&mut self) {
+        // Attempt to move whole files to the leftmost span of free space blocks that could fit the file
+        // Files are moved in decreasing order of file ID, starting with the highest
+        // If no span of free space to the left is large enough, the file does not move
+
+        // Space vacated by a file is never reused: it lies right of every file still to be moved
+        let state = &mut self.state;
+        state.next -= 1;
+        let file = &mut state.files[state.next];
+        file.span = state.free_spans.allocate(file.span.len, file.span.start).unwrap();
+        state.last_moved = Some(state.next);
+        self.skip_unmovable_files();
+    }
+
+    fn is_done(&self) -> bool {
+        self.state.next == 0
+    }
 
-    // Debug output or further processing
-    // println!("Resulting memory map: {:?}", memory_map.concat());
-    println!("Part two: {:?}", compute_checksum(memory_map));
+    /// First block of the file moved last, as drawn by `disk_frame`.
+    fn position(&self) -> Option<(usize, usize)> {
+        let start = self.state.files[self.state.last_moved?].span.start;
+        Some((start / DISK_IMAGE_WIDTH, start % DISK_IMAGE_WIDTH))
+    }
 }
 
-fn compute_checksum(memory_map: Vec<String>) -> usize {
-    let checksum: usize = memory_map
-        .iter()
-        .enumerate()
-        .filter_map(|(i, c)| c.parse::<usize>().ok().map(|file_id| i * file_id))
-        .sum();
+/// Moves whole files as in part two, handing the disk to `recorder` before the first move
+/// and after every move, and returns where the files end up.
+fn compact_files(files: &[FileSpan], free_spans: &FreeSpans, recorder: &mut Recorder) -> io::Result<Vec<FileSpan>> {
+    let disk_size = files.last().map_or(0, |file| file.span.end());
+    let mut compaction = FileCompaction::new(files, free_spans);
+    recorder.record(|| disk_image(&compaction.state.files, disk_size))?;
+    while !compaction.is_done() {
+        compaction.step();
+        recorder.record(|| disk_image(&compaction.state.files, disk_size))?;
+    }
+    Ok(compaction.state.files)
+}
 
-    return checksum
+pub fn part_two(input: &str) -> usize {
+    let (files, free_spans) = parse_disk(input);
+    let compacted = compact_files(&files, &free_spans, &mut Recorder::disabled()).unwrap();
+    compute_checksum(&compacted)
 }
 
+/// Records the whole-file compaction of part two.
+pub fn record(input: &str, recorder: &mut Recorder) -> io::Result<()> {
+    let (files, free_spans) = parse_disk(input);
+    compact_files(&files, &free_spans, recorder)?;
+    Ok(())
+}
 
-fn main() {
-    let line: &str = include_str!("../input/09.in");
-    let mut memory_map: Vec<String> = Vec::new();
-    let mut file_id = 0;
-    let mut chars = line.chars().peekable();
+/// Checkpoints hold the whole file list and free spans, so keep few of them: stepping back
+/// replays at most this many moves, which takes a few milliseconds.
+const VIEW_CHECKPOINT_EVERY: usize = 1024;
 
-    while let Some(file_size_char) = chars.next() {
-        // Parse file size
-        let file_size: usize = file_size_char.to_digit(10).unwrap() as usize;
+/// Steps through the whole-file compaction of part two, one move at a time, with a breakpoint
+/// on a file moving more than half the disk to the left.
+pub fn view(input: &str) -> Option<Box<dyn Viewer>> {
+    let (files, free_spans) = parse_disk(input);
+    let disk_size = files.last().map_or(0, |file| file.span.end());
+    let debugger = Debugger::new(FileCompaction::new(&files, &free_spans)).checkpoint_every(VIEW_CHECKPOINT_EVERY);
+    let starts: Vec<usize> = files.iter().map(|file| file.span.start).collect();
+    let moves_far = move |state: &CompactionState| {
+        state.last_moved.is_some_and(|i| starts[i] - state.files[i].span.start > disk_size / 2)
+    };
+    let render = move |compaction: &FileCompaction, step: usize| {
+        let compacted = &compaction.state.files;
+        let status = match compaction.state.last_moved {
+            None => format!("{} files on {} blocks, checksum {}", files.len(), disk_size, compute_checksum(&files)),
+            Some(i) => format!(
+                "move {}: file {} from block {} to block {}, checksum {}",
+                step, compacted[i].id, files[i].span.start, compacted[i].span.start, compute_checksum(compacted)
+            ),
+        };
+        Snapshot { frame: disk_frame(compacted, disk_size), status, focus: compaction.position() }
+    };
+    let viewer = DebuggerViewer::new(debugger, Box::new(render))
+        .with_condition("a file moved more than half the disk", moves_far);
+    Some(Box::new(viewer))
+}
 
-        // Parse free memory size if it exists
-        let free_mem: usize = chars.peek()
-            .and_then(|c| c.to_digit(10))
-            .map(|d| {
-                chars.next(); // Consume the character
-                d as usize
-            })
-            .unwrap_or(0);
+fn compute_checksum(files: &[FileSpan]) -> usize {
+    // Sum of `position * file_id` over all blocks, one span at a time
+    files
+        .iter()
+        .map(|file| {
+            let Span { start, len } = file.span;
+            file.id * (len * start + len * len.saturating_sub(1) / 2)
+        })
+        .sum()
+}
 
-        // Add file blocks to the memory map
-        memory_map.extend(vec![file_id.to_string(); file_size]);
+/// The disk block by block, `None` for free blocks.
+fn disk_blocks(files: &[FileSpan], disk_size: usize) -> Vec<Option<usize>> {
+    let mut blocks = vec![None; disk_size];
+    for file in files {
+        blocks[file.span.start..file.span.end()].fill(Some(file.id));
+    }
+    blocks
+}
 
-        // Add free space blocks to the memory map
-        memory_map.extend(vec![".".to_string(); free_mem]);
+fn block_checksum(blocks: &[Option<usize>]) -> usize {
+    blocks.iter().enumerate().map(|(position, id)| position * id.unwrap_or(0)).sum()
+}
 
-        // Increment the file ID
-        file_id += 1;
+/// Part one block by block: swap the last file block into the first free block until they meet.
+pub fn part_one_naive(input: &str) -> usize {
+    let (files, _) = parse_disk(input);
+    let mut blocks = disk_blocks(&files, files.last().map_or(0, |file| file.span.end()));
+    let (mut free, mut last) = (0, blocks.len());
+    loop {
+        while free < blocks.len() && blocks[free].is_some() {
+            free += 1;
+        }
+        while last > 0 && blocks[last - 1].is_none() {
+            last -= 1;
+        }
+        if last == 0 || free >= last - 1 {
+            break;
+        }
+        blocks.swap(free, last - 1);
     }
+    block_checksum(&blocks)
+}
 
-    // part_one(memory_map.clone());
-    part_two(memory_map.clone());
-}
\ No newline at end of file
+/// Part two block by block: scan the disk from the left for a free run for every file.
+pub fn part_two_naive(input: &str) -> usize {
+    let (files, _) = parse_disk(input);
+    let mut blocks = disk_blocks(&files, files.last().map_or(0, |file| file.span.end()));
+    for file in files.iter().rev() {
+        let Span { start, len } = file.span;
+        let mut run_start = 0;
+        for position in 0..start {
+            if blocks[position].is_some() {
+                run_start = position + 1;
+            } else if position + 1 - run_start == len {
+                blocks[start..start + len].fill(None);
+                blocks[run_start..run_start + len].fill(Some(file.id));
+                break;
+            }
+        }
+    }
+    block_checksum(&blocks)
+}
+
+/// A disk map of `size` digits: files of 1 to 9 blocks, free spans of 0 to 9 blocks.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let digits: String = (0..size)
+        .map(|i| {
+            let low = if i.is_multiple_of(2) { 1 } else { 0 };
+            char::from_digit(rng.range(low, 9) as u32, 10).unwrap()
+        })
+        .collect();
+    digits + "\n"
+}
+
+fn parse_disk(input: &str) -> (Vec<FileSpan>, FreeSpans) {
+    let disk_map: Vec<u8> = parse::digits(input).unwrap();
+    let mut files: Vec<FileSpan> = Vec::new();
+    let mut free_spans = FreeSpans::new();
+    let mut offset = 0;
+
+    // Digits alternate between the size of a file and the size of the free space after it
+    for (i, &size) in disk_map.iter().enumerate() {
+        let span = Span::new(offset, size as usize);
+        if i.is_multiple_of(2) {
+            files.push(FileSpan { id: i / 2, span });
+        } else {
+            free_spans.insert(span);
+        }
+        offset += span.len;
+    }
+
+    (files, free_spans)
+}
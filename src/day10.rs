@@ -1,18 +1,25 @@
-use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
 use queues::*;
+use crate::bitgrid::BitGrid;
+use crate::image::{HeightPalette, Image, Rgb};
+use crate::parse;
+use crate::random::Rng;
 
 // --- Day 10: Hoof It ---
 fn find_all_hiking_trail(
     trailhead: (usize, usize),
     topo_map: &[Vec<i32>],
+    visited: &mut BitGrid,
 ) -> usize {
     // Use BFS to find all hiking trails starting from the trailhead
     let mut num_trails = 0;
     let mut q: Queue<(usize, usize)> = queue![];
-    let mut visited = HashSet::new();
+    visited.clear();
 
     q.add(trailhead).unwrap();
-    visited.insert(trailhead);
+    visited.insert(trailhead.0, trailhead.1);
 
     while let Ok(current) = q.remove() {
         if topo_map[current.0][current.1] == 9 {
@@ -32,10 +39,9 @@ fn find_all_hiking_trail(
             if nx < topo_map.len()
                 && ny < topo_map[0].len()
                 && topo_map[nx][ny] == topo_map[current.0][current.1] + 1
-                && !visited.contains(&(nx, ny))
+                && visited.insert(nx, ny)
             {
                 q.add((nx, ny)).unwrap();
-                visited.insert((nx, ny));
             }
         }
     }
@@ -43,23 +49,51 @@ fn find_all_hiking_trail(
     num_trails
 }
 
-fn part_one(topo_map: Vec<Vec<i32>>) {
+/// Saves the height map with every position reachable from some trailhead highlighted.
+pub fn save_reachability_png(input: &str, path: &Path, scale: u32) -> io::Result<()> {
+    let (topo_map, trailheads) = parse_topo_map(input);
+    let mut visited = BitGrid::new(topo_map.len(), topo_map[0].len());
+    let mut reachable = BitGrid::new(topo_map.len(), topo_map[0].len());
+    for trailhead in trailheads {
+        find_all_hiking_trail(trailhead, &topo_map, &mut visited);
+        reachable.union_with(&visited);
+    }
+
+    let palette = HeightPalette { min: 0, max: 9, low: Rgb(16, 16, 48), high: Rgb(200, 200, 255), outside: Rgb::BLACK };
+    let mut image = Image::from_grid(&topo_map, &palette);
+    image.tint(reachable.iter(), Rgb::GREEN, 0.6);
+    image.write_png(path, scale)
+}
+
+/// The height map and its trailheads, the positions with height 0.
+fn parse_topo_map(input: &str) -> (Vec<Vec<i32>>, Vec<(usize, usize)>) {
+    // Parse the input map
+    // Impassable tiles (`.` in the examples) get a height no trail can reach
+    let topo_map: Vec<Vec<i32>> = parse::grid_of(input, |ch| {
+        Some(ch.to_digit(10).map_or(1000, |d| d as i32))
+    })
+    .unwrap();
     let mut trailheads = Vec::new();
 
     // Find all trailheads (positions with height 0)
-    for i in 0..topo_map.len() {
-        for j in 0..topo_map[i].len() {
-            if topo_map[i][j] == 0 {
+    for (i, row) in topo_map.iter().enumerate() {
+        for (j, &height) in row.iter().enumerate() {
+            if height == 0 {
                 trailheads.push((i, j));
             }
         }
     }
+    (topo_map, trailheads)
+}
 
+pub fn part_one(input: &str) -> usize {
+    let (topo_map, trailheads) = parse_topo_map(input);
     let mut total_trails = 0;
+    let mut visited = BitGrid::new(topo_map.len(), topo_map[0].len());
 
     // For each trailhead, calculate the number of unique reachable hiking trails
     for trailhead in trailheads {
-        let num_trails = find_all_hiking_trail(trailhead, &topo_map);
+        let num_trails = find_all_hiking_trail(trailhead, &topo_map, &mut visited);
         total_trails += num_trails;
         // println!(
         //     "Number of hiking trails from trailhead {:?}: {:?}",
@@ -67,19 +101,31 @@ fn part_one(topo_map: Vec<Vec<i32>>) {
         // );
     }
 
-    println!("Part one: Total trails = {:?}", total_trails);
+    total_trails
 }
 
-fn main() {
-    // Parse the input map
-    let topo_map: Vec<Vec<i32>> = include_str!("../input/10.in")
-        .lines()
-        .map(|line| {
-            line.chars()
-                .map(|ch| ch.to_digit(10).unwrap_or(1000) as i32)
-                .collect()
-        })
-        .collect();
-
-    part_one(topo_map);
-}
\ No newline at end of file
+/// A `size x size` topographic map of random heights, with `size / 2` hiking trails from
+/// a 0 to a 9 carved into it by random walks.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut heights: Vec<Vec<i64>> = (0..size).map(|_| (0..size).map(|_| rng.range(0, 9)).collect()).collect();
+    for _ in 0..size / 2 {
+        let (mut row, mut col) = (rng.index(size), rng.index(size));
+        let mut trail = vec![(row, col)];
+        for height in 0..=9 {
+            heights[row][col] = height;
+            // Continue to a neighbour that is not on the trail yet, so no height is overwritten
+            let neighbours: Vec<(usize, usize)> = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .into_iter()
+                .filter_map(|(dr, dc)| Some((row.checked_add_signed(dr)?, col.checked_add_signed(dc)?)))
+                .filter(|&(r, c)| r < size && c < size && !trail.contains(&(r, c)))
+                .collect();
+            if neighbours.is_empty() {
+                break;
+            }
+            (row, col) = *rng.choose(&neighbours);
+            trail.push((row, col));
+        }
+    }
+    heights.iter().map(|line| line.iter().map(|height| height.to_string()).collect::<String>() + "\n").collect()
+}
@@ -1,25 +1,27 @@
 use std::collections::HashMap;
 use rayon::prelude::*; // Import Rayon for parallel iterators
+use crate::days::Snapshot;
+use crate::digits;
+use crate::random::Rng;
+use crate::render::Frame;
 
 // --- Day 11: Plutonian Pebbles ---
 
-fn part_one(stones: Vec<i64>, num_blinkings: i32) {
-    let mut stones_tmp = stones.clone();
+pub fn part_one(input: &str) -> usize {
+    let num_blinkings = 25;
+    let mut stones_tmp = parse_stones(input);
     for _ in 0..num_blinkings {
         let mut stones_tmp_new: Vec<i64> = Vec::new();
         for stone in &stones_tmp {
             // If the stone is engraved with the number 0, it is replaced by a stone engraved with the number 1.
-            let stone_str = stone.abs().to_string();
+            let num_digits = digits::num_digits(*stone);
             if *stone == 0 {
                 stones_tmp_new.push(1);
             }
             // If the stone is engraved with a number that has an even number of digits, it is replaced by two stones.
             // The left half of the digits are engraved on the new left stone, and the right half of the digits are engraved on the new right stone
-            else if stone_str.len() % 2 == 0 {
-                let mid = stone_str.len() / 2;
-                // split str into two
-                let stone_first_half =  stone_str[..mid].parse::<i64>().unwrap();
-                let stone_second_half = stone_str[mid..].parse::<i64>().unwrap();
+            else if num_digits.is_multiple_of(2) {
+                let (stone_first_half, stone_second_half) = digits::split_at_digit(stone.abs(), num_digits / 2);
                 stones_tmp_new.push(stone_first_half);
                 stones_tmp_new.push(stone_second_half);
             }
@@ -29,77 +31,119 @@ fn part_one(stones: Vec<i64>, num_blinkings: i32) {
                 stones_tmp_new.push(stone*2024)
             }
         }
-        stones_tmp = stones_tmp_new.clone();
-        // println!("After {:?} blink: {:?}", blinking, stones_tmp)
-
+        stones_tmp = stones_tmp_new;
     }
 
+    stones_tmp.len()
+}
+
+/// Counts of stones per engraving after one blink.
+fn blink(stone_counts: &HashMap<i64, u64>) -> HashMap<i64, u64> {
+    // Use Rayon for parallel processing
+    stone_counts
+        .par_iter() // Parallel iterator over current stone_counts
+        .map(|(&stone, &count)| {
+            if stone == 0 {
+                // If the stone is 0, it transforms into a stone with value 1
+                vec![(1, count)]
+            } else {
+                let abs_stone = stone.abs();
+                let num_digits = digits::num_digits(abs_stone);
+
+                if num_digits.is_multiple_of(2) {
+                    // Split the stone into two halves
+                    let (stone_first_half, stone_second_half) = digits::split_at_digit(abs_stone, num_digits / 2);
 
-    println!("Part one: {:?}", stones_tmp.len())
+                    vec![(stone_first_half, count), (stone_second_half, count)]
+                } else {
+                    // Multiply the stone
+                    let new_value = stone * 2024;
+                    vec![(new_value, count)]
+                }
+            }
+        })
+        .flatten() // Flatten the Vec<Vec<(stone, count)>> into Vec<(stone, count)>
+        .fold(HashMap::new, |mut acc, (stone, count)| {
+            // Combine results into a HashMap
+            *acc.entry(stone).or_insert(0) += count;
+            acc
+        })
+        .reduce(HashMap::new, |mut acc, partial| {
+            // Merge partial results
+            for (stone, count) in partial {
+                *acc.entry(stone).or_insert(0) += count;
+            }
+            acc
+        })
 }
 
-fn part_two(stones: Vec<i64>, num_blinkings: i32) {
+fn count_stones(input: &str) -> HashMap<i64, u64> {
     let mut stone_counts: HashMap<i64, u64> = HashMap::new();
 
     // Initialize the HashMap with the initial stones
-    for stone in stones {
+    for stone in parse_stones(input) {
         *stone_counts.entry(stone).or_insert(0) += 1;
     }
+    stone_counts
+}
 
-    for blinking in 0..num_blinkings {
-        println!("Blinking {} times", blinking);
+pub fn part_two(input: &str) -> u64 {
+    let mut stone_counts = count_stones(input);
+    for _ in 0..75 {
+        stone_counts = blink(&stone_counts);
+    }
 
-        // Use Rayon for parallel processing
-        let new_stone_counts: HashMap<i64, u64> = stone_counts
-            .par_iter() // Parallel iterator over current stone_counts
-            .map(|(&stone, &count)| {
-                if stone == 0 {
-                    // If the stone is 0, it transforms into a stone with value 1
-                    vec![(1, count)]
-                } else {
-                    let abs_stone = stone.abs();
-                    let num_digits = ((abs_stone as f64).log10() as u32) + 1; // Efficient digit counting
-
-                    if num_digits % 2 == 0 {
-                        // Split the stone into two halves
-                        let half_power = 10i64.pow(num_digits / 2);
-                        let stone_first_half = abs_stone / half_power;
-                        let stone_second_half = abs_stone % half_power;
-
-                        vec![(stone_first_half, count), (stone_second_half, count)]
-                    } else {
-                        // Multiply the stone
-                        let new_value = stone * 2024;
-                        vec![(new_value, count)]
-                    }
-                }
-            })
-            .flatten() // Flatten the Vec<Vec<(stone, count)>> into Vec<(stone, count)>
-            .fold(HashMap::new, |mut acc, (stone, count)| {
-                // Combine results into a HashMap
-                *acc.entry(stone).or_insert(0) += count;
-                acc
-            })
-            .reduce(HashMap::new, |mut acc, partial| {
-                // Merge partial results
-                for (stone, count) in partial {
-                    *acc.entry(stone).or_insert(0) += count;
-                }
-                acc
-            });
+    // Compute the total number of stones
+    stone_counts.values().sum::<u64>()
+}
 
-        stone_counts = new_stone_counts;
+/// Part one with the counting map of part two instead of the explicit list of stones.
+pub fn part_one_counting(input: &str) -> u64 {
+    let mut stone_counts = count_stones(input);
+    for _ in 0..25 {
+        stone_counts = blink(&stone_counts);
     }
+    stone_counts.values().sum::<u64>()
+}
 
-    // Compute the total number of stones
-    println!("Part two: {:?}", stone_counts.iter().map(|(_, v)| *v).sum::<u64>());
+/// `size` stones engraved with numbers of 1 to 8 digits, with the occasional 0.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let stones: Vec<String> = (0..size)
+        .map(|_| match rng.chance(0.1) {
+            true => 0,
+            false => {
+                let num_digits = rng.range(1, 8) as u32;
+                rng.range(10i64.pow(num_digits - 1), 10i64.pow(num_digits) - 1)
+            }
+        })
+        .map(|stone| stone.to_string())
+        .collect();
+    stones.join(" ") + "\n"
 }
 
-fn main() {
-    // Parse the input map
-    let stones: Vec<i64> = include_str!("../input/11.in").split_whitespace().map(|x| x.parse::<i64>().unwrap()).collect();
+/// Stones per engraving after `step` blinks, most common first. Stops after the 75 blinks of part two.
+pub fn snapshot(input: &str, step: usize) -> Option<Snapshot> {
+    if step > 75 {
+        return None;
+    }
+    let mut stone_counts = count_stones(input);
+    for _ in 0..step {
+        stone_counts = blink(&stone_counts);
+    }
+
+    let mut engravings: Vec<(i64, u64)> = stone_counts.iter().map(|(&stone, &count)| (stone, count)).collect();
+    engravings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let lines: Vec<String> = engravings.iter().map(|(stone, count)| format!("{:>20} x {}", stone, count)).collect();
+
+    let status = format!(
+        "blink {}: {} stones, {} distinct engravings",
+        step,
+        stone_counts.values().sum::<u64>(),
+        stone_counts.len()
+    );
+    Some(Snapshot { frame: Frame::from_text(&lines.join("\n")), status, focus: None })
+}
 
-    // part_one(stones.clone(), 25);
-    part_two(stones.clone(), 75);
-    // part_two();
+fn parse_stones(input: &str) -> Vec<i64> {
+    input.split_whitespace().map(|x| x.parse::<i64>().unwrap()).collect()
 }
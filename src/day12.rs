@@ -1,45 +1,15 @@
-use std::collections::{HashMap, HashSet};
-
-/// --- Day 12: Garden Groups ---
-
-/// Depth-first search to find connected components (regions) of the same plant type.
-fn dfs(
-    garden: &Vec<Vec<char>>,
-    visited: &mut Vec<Vec<bool>>,
-    position: (usize, usize),
-) -> Vec<(usize, usize)> {
-    let (start_i, start_j) = position;
-    let mut stack = vec![(start_i, start_j)];
-    let mut component = vec![];
-
-    while let Some((ci, cj)) = stack.pop() {
-        if visited[ci][cj] {
-            continue;
-        }
-        visited[ci][cj] = true;
-        component.push((ci, cj));
-
-        // Check neighbors (up, down, left, right)
-        let neighbors = [
-            (ci.wrapping_sub(1), cj),
-            (ci + 1, cj),
-            (ci, cj.wrapping_sub(1)),
-            (ci, cj + 1),
-        ];
-
-        for &(ni, nj) in &neighbors {
-            if ni < garden.len()
-                && nj < garden[0].len()
-                && !visited[ni][nj]
-                && garden[ni][nj] == garden[ci][cj]
-            {
-                stack.push((ni, nj));
-            }
-        }
-    }
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
 
-    component
-}
+use crate::days::Snapshot;
+use crate::disjoint_set::{label_components, Connectivity, Labelling, Region};
+use crate::image::{Image, Rgb};
+use crate::random::Rng;
+use crate::render::Frame;
+use crate::svg::Svg;
+
+// --- Day 12: Garden Groups ---
 
 // ---------------------------------------------------------------------------
 // PART ONE: Perimeter-Based Fence Price
@@ -47,63 +17,43 @@ fn dfs(
 
 /// Computes the fence price for a region by using `area * perimeter`.
 /// The perimeter is counted as the total number of edges that border
-/// out-of-bounds or a different region type.
-fn compute_perimeter_price(region: &Vec<(usize, usize)>, garden: &Vec<Vec<char>>) -> i32 {
-    let area = region.len(); // number of cells
+/// out-of-bounds or a different region.
+fn compute_perimeter_price(region: &Region, labels: &[Vec<usize>]) -> i32 {
+    let rows = labels.len();
+    let cols = labels[0].len();
     let mut perimeter = 0;
 
-    let rows = garden.len();
-    let cols = garden[0].len();
-
-    // Put all cells of this region into a HashSet for quick lookup
-    let region_set: HashSet<(usize, usize)> = region.iter().copied().collect();
-
     // For each cell in the region, check its 4 neighbors.
     // If the neighbor is out of bounds or not in the same region, perimeter++
-    for &(r, c) in &region_set {
+    for &(r, c) in &region.cells {
         for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
             let nr = r as isize + dr;
             let nc = c as isize + dc;
 
-            // Out of bounds => perimeter edge
-            if nr < 0 || nc < 0 || nr >= rows as isize || nc >= cols as isize {
-                perimeter += 1;
-            }
-            // Different region => perimeter edge
-            else if !region_set.contains(&(nr as usize, nc as usize)) {
+            if nr < 0
+                || nc < 0
+                || nr >= rows as isize
+                || nc >= cols as isize
+                || labels[nr as usize][nc as usize] != region.label
+            {
                 perimeter += 1;
             }
         }
     }
 
-    (area * perimeter) as i32
+    (region.area * perimeter) as i32
 }
 
-/// Finds connected components (regions) in `garden` and computes the total fence
-/// price using the perimeter-based cost.
-fn part_one(garden: &Vec<Vec<char>>) {
-    let rows = garden.len();
-    let cols = garden[0].len();
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut components = Vec::new();
-
-    // Identify all connected regions via DFS
-    for i in 0..rows {
-        for j in 0..cols {
-            if !visited[i][j] {
-                let component = dfs(garden, &mut visited, (i, j));
-                components.push(component);
-            }
-        }
-    }
-
-    // Sum perimeter-based fence prices
-    let total_price: i32 = components
+/// Computes the total fence price of all regions using the perimeter-based cost.
+pub fn part_one(input: &str) -> i32 {
+    let (_, labelling) = parse_garden(input);
+    let total_price: i32 = labelling
+        .regions
         .iter()
-        .map(|region| compute_perimeter_price(region, garden))
+        .map(|region| compute_perimeter_price(region, &labelling.labels))
         .sum();
 
-    println!("Part one: {}", total_price);
+    total_price
 }
 
 // ---------------------------------------------------------------------------
@@ -162,7 +112,7 @@ fn count_runs_in_sorted_list(sorted_vals: &[usize]) -> usize {
 
 /// Computes the fence price using the bulk-discount rule: `area * number_of_sides`.
 /// Here, each continuous straight line (top, bottom, left, right) is counted as 1 side.
-fn compute_bulk_fence_price(region: &[(usize, usize)], garden: &Vec<Vec<char>>) -> i32 {
+fn compute_bulk_fence_price(region: &[(usize, usize)], garden: &[Vec<char>]) -> i32 {
     let area = region.len();
     let rows = garden.len();
     let cols = garden[0].len();
@@ -204,40 +154,190 @@ fn compute_bulk_fence_price(region: &[(usize, usize)], garden: &Vec<Vec<char>>)
     (area * total_sides) as i32
 }
 
-/// Finds connected components (regions) in `garden` and computes the total fence
-/// price using the bulk discount cost.
-fn part_two(garden: &Vec<Vec<char>>) {
+/// Computes the total fence price of all regions using the bulk discount cost.
+pub fn part_two(input: &str) -> i32 {
+    let (garden, labelling) = parse_garden(input);
+    let total_price: i32 = labelling
+        .regions
+        .iter()
+        .map(|region| compute_bulk_fence_price(&region.cells, &garden))
+        .sum();
+
+    total_price
+}
+
+/// The garden with every region in its own colour.
+pub fn snapshot(input: &str, step: usize) -> Option<Snapshot> {
+    if step > 0 {
+        return None;
+    }
+    let (garden, labelling) = parse_garden(input);
+    let mut frame = Frame::from_grid(&garden, |&plant| plant);
+    frame.color_regions(&labelling.labels);
+    let status = format!("{} regions", labelling.regions.len());
+    Some(Snapshot { frame, status, focus: None })
+}
+
+/// Saves the garden as a PNG with every region in its own colour.
+pub fn save_regions_png(input: &str, path: &Path, scale: u32) -> io::Result<()> {
+    let (_, labelling) = parse_garden(input);
+    let rows = labelling.labels.len();
+    let cols = labelling.labels[0].len();
+    let mut image = Image::filled(rows, cols, Rgb::BLACK);
+    image.color_regions(&labelling.labels);
+    image.write_png(path, scale)
+}
+
+/// Unit fence segments of a region grouped by (side of the cell, grid line), holding the
+/// sorted offsets along the line. Sides: 0 = top, 1 = bottom (horizontal lines),
+/// 2 = left, 3 = right (vertical lines).
+fn fence_segments(region: &Region, labels: &[Vec<usize>]) -> BTreeMap<(u8, usize), Vec<usize>> {
+    let rows = labels.len() as isize;
+    let cols = labels[0].len() as isize;
+    let is_outside = |r: isize, c: isize| {
+        r < 0 || c < 0 || r >= rows || c >= cols || labels[r as usize][c as usize] != region.label
+    };
+
+    let mut segments: BTreeMap<(u8, usize), Vec<usize>> = BTreeMap::new();
+    for &(r, c) in &region.cells {
+        let (ri, ci) = (r as isize, c as isize);
+        if is_outside(ri - 1, ci) {
+            segments.entry((0, r)).or_default().push(c);
+        }
+        if is_outside(ri + 1, ci) {
+            segments.entry((1, r + 1)).or_default().push(c);
+        }
+        if is_outside(ri, ci - 1) {
+            segments.entry((2, c)).or_default().push(r);
+        }
+        if is_outside(ri, ci + 1) {
+            segments.entry((3, c + 1)).or_default().push(r);
+        }
+    }
+    for offsets in segments.values_mut() {
+        offsets.sort_unstable();
+    }
+    segments
+}
+
+/// The line between grid corners `(x, y)` along `line` of the given cell side, from `start` to `end`.
+fn fence_line(side: u8, line: usize, start: usize, end: usize) -> [(usize, usize); 2] {
+    match side {
+        0 | 1 => [(start, line), (end, line)],
+        _ => [(line, start), (line, end)],
+    }
+}
+
+/// Straight fence sides of a region as line segments between grid corners `(x, y)`.
+/// Computed from the region labels, independently of `count_horizontal_runs`
+/// and `count_vertical_runs`, so the two can be checked against each other.
+fn fence_sides(segments: &BTreeMap<(u8, usize), Vec<usize>>) -> Vec<[(usize, usize); 2]> {
+    // Consecutive unit segments on the same line and cell side form one straight side
+    let mut sides = Vec::new();
+    for (&(side, line), offsets) in segments {
+        let mut run_start = offsets[0];
+        for (i, &offset) in offsets.iter().enumerate() {
+            let is_run_end = i + 1 == offsets.len() || offsets[i + 1] != offset + 1;
+            if is_run_end {
+                sides.push(fence_line(side, line, run_start, offset + 1));
+                if i + 1 < offsets.len() {
+                    run_start = offsets[i + 1];
+                }
+            }
+        }
+    }
+    sides
+}
+
+/// Saves the garden as an SVG: regions filled in distinct colours and labelled with their
+/// plant, every unit fence segment drawn thin, and every straight side drawn over them as
+/// one polyline. Hovering a region shows its statistics.
+pub fn save_regions_svg(input: &str, path: &Path) -> io::Result<()> {
+    const CELL: f64 = 10.0;
+    let (garden, labelling) = parse_garden(input);
     let rows = garden.len();
     let cols = garden[0].len();
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut components = Vec::new();
-
-    // Identify all connected regions via DFS
-    for i in 0..rows {
-        for j in 0..cols {
-            if !visited[i][j] {
-                let component = dfs(garden, &mut visited, (i, j));
-                components.push(component);
+    let mut svg = Svg::new(cols as f64 * CELL, rows as f64 * CELL);
+    let corner = |(x, y): (usize, usize)| (x as f64 * CELL, y as f64 * CELL);
+
+    for region in &labelling.regions {
+        let (r0, c0) = region.cells[0];
+        let segments = fence_segments(region, &labelling.labels);
+        let sides = fence_sides(&segments);
+        let perimeter: usize = segments.values().map(Vec::len).sum();
+        let bulk_price = compute_bulk_fence_price(&region.cells, &garden) as usize;
+        let tooltip = format!(
+            "{}: area {}, perimeter {}, sides {} ({} drawn), price {} (bulk {})",
+            garden[r0][c0],
+            region.area,
+            perimeter,
+            bulk_price / region.area,
+            sides.len(),
+            region.area * perimeter,
+            bulk_price,
+        );
+
+        svg.begin_group(&format!(r#"fill="{}""#, Rgb::from_label(region.label).hex()), Some(&tooltip));
+        for &(r, c) in &region.cells {
+            svg.rect(c as f64 * CELL, r as f64 * CELL, CELL, CELL, "");
+        }
+        for (&(side, line), offsets) in &segments {
+            for &offset in offsets {
+                let [from, to] = fence_line(side, line, offset, offset + 1);
+                svg.polyline(&[corner(from), corner(to)], r#"stroke="dimgray" stroke-width="0.5""#);
             }
         }
+        for [from, to] in sides {
+            svg.polyline(&[corner(from), corner(to)], r#"stroke="black" stroke-width="1.5" stroke-linecap="round""#);
+        }
+        // Labelled in the region's first cell in row-major order
+        let (x, y) = ((c0 as f64 + 0.5) * CELL, (r0 as f64 + 0.5) * CELL);
+        let label = garden[r0][c0].to_string();
+        svg.text(x, y, &label, r#"fill="black" font-size="8" text-anchor="middle" dominant-baseline="central""#);
+        svg.end_group();
     }
 
-    // Sum bulk-discount fence prices
-    let total_price: i32 = components
-        .iter()
-        .map(|region| compute_bulk_fence_price(region, garden))
-        .sum();
-
-    println!("Part two: {}", total_price);
+    svg.write(path)
 }
 
-fn main() {
+fn parse_garden(input: &str) -> (Vec<Vec<char>>, Labelling) {
     // Parse the input map
-    let garden: Vec<Vec<char>> = include_str!("../input/12.in")
+    let garden: Vec<Vec<char>> = input
         .lines()
         .map(|line| line.chars().collect())
         .collect();
 
-    part_one(&garden); // perimeter-based price
-    part_two(&garden); // bulk-discount price
-}
\ No newline at end of file
+    // Identify all connected regions of the same plant type
+    let labelling = label_components(&garden, Connectivity::Four, |a, b| a == b);
+    (garden, labelling)
+}
+
+/// A `size x size` garden: regions grow from one seed per 20 plots, each seed with a random
+/// plant, by assigning plots next to already planted ones in random order. Neighbouring
+/// regions of the same plant merge, so regions come in irregular shapes.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut garden: Vec<Vec<Option<char>>> = vec![vec![None; size]; size];
+    let mut frontier: Vec<(usize, usize, char)> = Vec::new();
+    for _ in 0..(size * size / 20).max(1) {
+        let plant = (b'A' + rng.below(26) as u8) as char;
+        frontier.push((rng.index(size), rng.index(size), plant));
+    }
+
+    while !frontier.is_empty() {
+        let (row, col, plant) = frontier.swap_remove(rng.index(frontier.len()));
+        if garden[row][col].is_some() {
+            continue;
+        }
+        garden[row][col] = Some(plant);
+        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            if let (Some(r), Some(c)) = (row.checked_add_signed(dr), col.checked_add_signed(dc)) {
+                if r < size && c < size && garden[r][c].is_none() {
+                    frontier.push((r, c, plant));
+                }
+            }
+        }
+    }
+
+    garden.iter().map(|line| line.iter().map(|plant| plant.unwrap()).collect::<String>() + "\n").collect()
+}
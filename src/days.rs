@@ -0,0 +1,396 @@
+// Registry of the implemented days, shared by the `aoc` command line and the TUI.
+//
+// Every part takes the puzzle input and returns its answer as text. Everything else a
+// day offers is optional, one `Day` field each: see the field docs.
+
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::time::{Duration, Instant};
+
+use crate::random::Rng;
+use crate::recorder::Recorder;
+use crate::render::Frame;
+use crate::viewer::{SnapshotViewer, Viewer};
+use crate::{day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12};
+
+/// The state of a day at one simulation step.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub frame: Frame,
+    /// One line describing the state, e.g. the step and position.
+    pub status: String,
+    /// `(row, col)` to keep in view, e.g. the guard.
+    pub focus: Option<(usize, usize)>,
+}
+
+pub type Part = fn(&str) -> String;
+
+/// Returns the state after `step` steps, or `None` once the simulation has ended.
+pub type SnapshotFn = fn(&str, usize) -> Option<Snapshot>;
+
+/// Opens a viewer at the first state for the input, or `None` if there are no states.
+pub type OpenView = fn(&str) -> Option<Box<dyn Viewer>>;
+
+/// Saves a picture of the puzzle solved for the input as a PNG, `scale` pixels per cell.
+pub type SavePng = fn(&str, &Path, u32) -> io::Result<()>;
+
+/// Saves a vector picture of the puzzle solved for the input as an SVG.
+pub type SaveSvg = fn(&str, &Path) -> io::Result<()>;
+
+/// Runs the day's simulation on the input, handing every step to the recorder.
+pub type Record = fn(&str, &mut Recorder) -> io::Result<()>;
+
+/// Writes a random puzzle input; `size` is the main dimension, such as the number of lines.
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+/// Limits for a [`Stream`] solver.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// Bytes the solver may hold; `None` lets it keep the parsed input in memory.
+    pub memory: Option<usize>,
+    /// Where solvers with a memory limit spill to.
+    pub temp_dir: PathBuf,
+}
+
+impl Default for StreamOptions {
+    fn default() -> Self {
+        StreamOptions { memory: None, temp_dir: env::temp_dir() }
+    }
+}
+
+/// Answers of a [`Stream`] solver, with extra lines to show such as memory statistics.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Streamed {
+    pub answers: Vec<String>,
+    pub details: Vec<String>,
+}
+
+/// Answers every part reading the input from `reader` as it goes, for inputs too large to hold as text.
+pub type Stream = fn(&mut dyn BufRead, &StreamOptions) -> io::Result<Streamed>;
+
+/// A further named implementation of one part, e.g. a naive version of an optimised one.
+#[derive(Debug, Clone, Copy)]
+pub struct Implementation {
+    pub name: &'static str,
+    pub part: u32,
+    pub solve: Part,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Day {
+    pub number: u32,
+    pub title: &'static str,
+    pub part_one: Part,
+    pub part_two: Option<Part>,
+    /// Steps through the states of the day's simulation, for the TUI and `aoc show`.
+    pub view: Option<OpenView>,
+    /// A picture of the solved puzzle, for `aoc png`.
+    pub png: Option<SavePng>,
+    /// A vector picture of the solved puzzle, for `aoc svg`.
+    pub svg: Option<SaveSvg>,
+    /// A recording of the simulation as a GIF or PNG frames, for `aoc record`.
+    pub record: Option<Record>,
+    /// Checked against `part_one` and `part_two`, which are named `default`, by `aoc crosscheck`.
+    pub alternatives: &'static [Implementation],
+    /// Random inputs for `aoc crosscheck` and `aoc gen`.
+    pub generate: Option<Generator>,
+    /// Solves inputs too large to hold, from a file or stdin, for `aoc stream`.
+    pub stream: Option<Stream>,
+}
+
+impl Day {
+    pub fn part(&self, part: u32) -> Option<Part> {
+        match part {
+            1 => Some(self.part_one),
+            2 => self.part_two,
+            _ => None,
+        }
+    }
+
+    /// Every implementation of `part` with its name, the default one first.
+    pub fn implementations(&self, part: u32) -> Vec<(&'static str, Part)> {
+        let alternatives = self.alternatives.iter().filter(|alternative| alternative.part == part);
+        self.part(part)
+            .map(|solve| ("default", solve))
+            .into_iter()
+            .chain(alternatives.map(|alternative| (alternative.name, alternative.solve)))
+            .collect()
+    }
+
+    /// `input/NN.in` in the repository.
+    pub fn input_path(&self) -> PathBuf {
+        PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/input")).join(format!("{:02}.in", self.number))
+    }
+
+    pub fn input(&self) -> io::Result<String> {
+        fs::read_to_string(self.input_path())
+    }
+}
+
+pub const DAYS: [Day; 12] = [
+    Day {
+        number: 1,
+        title: "Historian Hysteria",
+        part_one: |input| day01::part_one(input).to_string(),
+        part_two: Some(|input| day01::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day01::generate),
+        stream: Some(|reader, options| match options.memory {
+            None => {
+                let (distance, similarity) = day01::solve_stream(reader)?;
+                Ok(Streamed { answers: vec![distance.to_string(), similarity.to_string()], details: vec![] })
+            }
+            Some(memory) => {
+                let answers = day01::solve_external(reader, memory, &options.temp_dir)?;
+                Ok(Streamed {
+                    answers: vec![answers.distance.to_string(), answers.similarity.to_string()],
+                    details: vec![format!(
+                        "{} runs, estimated peak memory {} KiB",
+                        answers.runs,
+                        answers.estimated_peak_memory / 1024
+                    )],
+                })
+            }
+        }),
+    },
+    Day {
+        number: 2,
+        title: "Red-Nosed Reports",
+        part_one: |input| day02::part_one(input).to_string(),
+        part_two: Some(|input| day02::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[
+            Implementation { name: "brute-force", part: 2, solve: |input| day02::part_two_brute_force(input).to_string() },
+            Implementation { name: "linear", part: 2, solve: |input| day02::part_two_linear(input).to_string() },
+        ],
+        generate: Some(day02::generate),
+        stream: None,
+    },
+    Day {
+        number: 3,
+        title: "Mull It Over",
+        part_one: |input| day03::part_one(input).to_string(),
+        part_two: Some(|input| day03::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day03::generate),
+        stream: Some(|reader, _| {
+            let (state, counts) = day03::solve_stream(reader)?;
+            let counts: Vec<String> = counts.iter().map(|(name, count)| format!("{} {}", name, count)).collect();
+            Ok(Streamed {
+                answers: vec![state.total.to_string(), state.enabled_total.to_string()],
+                details: vec![format!("executed: {}", counts.join(", "))],
+            })
+        }),
+    },
+    Day {
+        number: 4,
+        title: "Ceres Search",
+        part_one: |input| day04::part_one(input).to_string(),
+        part_two: Some(|input| day04::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day04::generate),
+        stream: None,
+    },
+    Day {
+        number: 5,
+        title: "Print Queue",
+        part_one: |input| day05::part_one(input).to_string(),
+        part_two: Some(|input| day05::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day05::generate),
+        stream: None,
+    },
+    Day {
+        number: 6,
+        title: "Guard Gallivant",
+        part_one: |input| day06::part_one(input).to_string(),
+        part_two: Some(|input| day06::part_two(input).to_string()),
+        view: Some(day06::view),
+        png: Some(day06::save_maze_png),
+        svg: None,
+        record: Some(day06::record),
+        alternatives: &[],
+        generate: Some(day06::generate),
+        stream: None,
+    },
+    Day {
+        number: 7,
+        title: "Bridge Repair",
+        part_one: |input| day07::part_one(input).to_string(),
+        part_two: Some(|input| day07::part_two(input).to_string()),
+        view: None,
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day07::generate),
+        stream: None,
+    },
+    Day {
+        number: 8,
+        title: "Resonant Collinearity",
+        part_one: |input| day08::part_one(input).to_string(),
+        part_two: Some(|input| day08::part_two(input).to_string()),
+        view: Some(|input| SnapshotViewer::open(input, day08::snapshot)),
+        png: Some(day08::save_map_png),
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day08::generate),
+        stream: None,
+    },
+    Day {
+        number: 9,
+        title: "Disk Fragmenter",
+        part_one: |input| day09::part_one(input).to_string(),
+        part_two: Some(|input| day09::part_two(input).to_string()),
+        view: Some(day09::view),
+        png: None,
+        svg: None,
+        record: Some(day09::record),
+        alternatives: &[
+            Implementation { name: "naive", part: 1, solve: |input| day09::part_one_naive(input).to_string() },
+            Implementation { name: "naive", part: 2, solve: |input| day09::part_two_naive(input).to_string() },
+        ],
+        generate: Some(day09::generate),
+        stream: None,
+    },
+    Day {
+        number: 10,
+        title: "Hoof It",
+        part_one: |input| day10::part_one(input).to_string(),
+        part_two: None,
+        view: None,
+        png: Some(day10::save_reachability_png),
+        svg: None,
+        record: None,
+        alternatives: &[],
+        generate: Some(day10::generate),
+        stream: None,
+    },
+    Day {
+        number: 11,
+        title: "Plutonian Pebbles",
+        part_one: |input| day11::part_one(input).to_string(),
+        part_two: Some(|input| day11::part_two(input).to_string()),
+        view: Some(|input| SnapshotViewer::open(input, day11::snapshot)),
+        png: None,
+        svg: None,
+        record: None,
+        alternatives: &[Implementation { name: "counting", part: 1, solve: |input| day11::part_one_counting(input).to_string() }],
+        generate: Some(day11::generate),
+        stream: None,
+    },
+    Day {
+        number: 12,
+        title: "Garden Groups",
+        part_one: |input| day12::part_one(input).to_string(),
+        part_two: Some(|input| day12::part_two(input).to_string()),
+        view: Some(|input| SnapshotViewer::open(input, day12::snapshot)),
+        png: Some(day12::save_regions_png),
+        svg: Some(day12::save_regions_svg),
+        record: None,
+        alternatives: &[],
+        generate: Some(day12::generate),
+        stream: None,
+    },
+];
+
+pub fn find(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+/// Result of running one part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Answer(String, Duration),
+    Failed(String),
+}
+
+impl Outcome {
+    /// The answer, or the failure message as an error; timings are left out so outcomes can be compared.
+    pub fn result(&self) -> Result<&str, &str> {
+        match self {
+            Outcome::Answer(answer, _) => Ok(answer),
+            Outcome::Failed(message) => Err(message),
+        }
+    }
+}
+
+/// Runs a part on the day's input.
+pub fn run_part(day: &Day, part: u32) -> Outcome {
+    let Some(solve) = day.part(part) else {
+        return Outcome::Failed(format!("day {} has no part {}", day.number, part));
+    };
+    match day.input() {
+        Ok(input) => run_caught(solve, &input),
+        Err(err) => Outcome::Failed(format!("{}: {}", day.input_path().display(), err)),
+    }
+}
+
+thread_local! {
+    /// Set while `run_caught` runs a part on this thread.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs, once per process, a panic hook that keeps quiet about panics `run_caught` is
+/// catching and hands every other panic to the hook that was there before.
+fn install_quiet_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(Cell::get) {
+                previous(info);
+            }
+        }));
+    });
+}
+
+/// Runs a part on `input`, catching panics so that a failing part shows up as
+/// an error instead of taking the caller down.
+pub fn run_caught(solve: Part, input: &str) -> Outcome {
+    // The default hook would print every panic message, e.g. over the TUI
+    install_quiet_hook();
+    let catching = CATCHING.replace(true);
+    let start = Instant::now();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| solve(input)));
+    let elapsed = start.elapsed();
+    CATCHING.set(catching);
+
+    match result {
+        Ok(answer) => Outcome::Answer(answer, elapsed),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            Outcome::Failed(format!("panicked: {}", message))
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Decimal digit arithmetic on integers, without going through strings.
+//
+// Signed values are handled by their absolute value: `-1234` has 4 digits,
+// and splitting keeps the sign on both halves (`-1234` -> `(-12, -34)`).
+
+pub trait Digits: Copy + Sized {
+    /// Number of decimal digits; `0` has one digit.
+    fn num_digits(self) -> u32;
+
+    /// `10^exp`, or `None` if it does not fit.
+    fn pow10(exp: u32) -> Option<Self>;
+
+    /// Appends the digits of `other` to `self`, e.g. `12 || 345 = 12345`.
+    /// Returns `None` on overflow or if `other` is negative.
+    fn concat(self, other: Self) -> Option<Self>;
+
+    /// Splits off the `k` lowest digits: `(self / 10^k, self % 10^k)`.
+    fn split_at_digit(self, k: u32) -> (Self, Self);
+}
+
+macro_rules! impl_unsigned_digits {
+    ($($t:ty),*) => {$(
+        impl Digits for $t {
+            fn num_digits(self) -> u32 {
+                self.checked_ilog10().map_or(1, |log| log + 1)
+            }
+
+            fn pow10(exp: u32) -> Option<Self> {
+                (10 as $t).checked_pow(exp)
+            }
+
+            fn concat(self, other: Self) -> Option<Self> {
+                self.checked_mul(Self::pow10(other.num_digits())?)?.checked_add(other)
+            }
+
+            fn split_at_digit(self, k: u32) -> (Self, Self) {
+                match Self::pow10(k) {
+                    Some(divisor) => (self / divisor, self % divisor),
+                    // 10^k does not even fit in the type, so every digit is in the low part
+                    None => (0, self),
+                }
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed_digits {
+    ($($t:ty),*) => {$(
+        impl Digits for $t {
+            fn num_digits(self) -> u32 {
+                self.unsigned_abs().num_digits()
+            }
+
+            fn pow10(exp: u32) -> Option<Self> {
+                (10 as $t).checked_pow(exp)
+            }
+
+            fn concat(self, other: Self) -> Option<Self> {
+                if other < 0 {
+                    return None;
+                }
+                let shifted = self.checked_mul(Self::pow10(other.num_digits())?)?;
+                if self < 0 {
+                    shifted.checked_sub(other)
+                } else {
+                    shifted.checked_add(other)
+                }
+            }
+
+            fn split_at_digit(self, k: u32) -> (Self, Self) {
+                match Self::pow10(k) {
+                    Some(divisor) => (self / divisor, self % divisor),
+                    None => (0, self),
+                }
+            }
+        }
+    )*};
+}
+
+impl_unsigned_digits!(u32, u64, u128, usize);
+impl_signed_digits!(i32, i64, i128);
+
+pub fn num_digits<T: Digits>(n: T) -> u32 {
+    n.num_digits()
+}
+
+pub fn pow10<T: Digits>(exp: u32) -> Option<T> {
+    T::pow10(exp)
+}
+
+pub fn concat<T: Digits>(a: T, b: T) -> Option<T> {
+    a.concat(b)
+}
+
+pub fn split_at_digit<T: Digits>(n: T, k: u32) -> (T, T) {
+    n.split_at_digit(k)
+}
@@ -0,0 +1,203 @@
+// Disjoint-set (union-find) with path compression and union by rank,
+// plus connected-component labelling of 2D grids on top of it.
+
+/// Which neighbouring cells are considered adjacent when labelling a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, down, left, right.
+    Four,
+    /// The four orthogonal neighbours plus the four diagonals.
+    Eight,
+}
+
+impl Connectivity {
+    /// Neighbour offsets that point "backwards" in row-major order.
+    /// Looking only at these is enough to union every adjacent pair exactly once.
+    fn backward_offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(-1, 0), (0, -1)],
+            Connectivity::Eight => &[(-1, -1), (-1, 0), (-1, 1), (0, -1)],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    num_sets: usize,
+}
+
+impl DisjointSet {
+    /// Creates `n` singleton sets `{0}, {1}, ..., {n - 1}`.
+    pub fn new(n: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            num_sets: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Number of disjoint sets currently tracked.
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Returns the representative of the set containing `x`,
+    /// pointing every node on the way directly at it.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Path compression
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`.
+    /// Returns `false` if they were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let root_a = self.find(a);
+        let root_b = self.find(b);
+        if root_a == root_b {
+            return false;
+        }
+
+        // Union by rank: hang the shallower tree below the deeper one
+        let (root, child) = if self.rank[root_a] < self.rank[root_b] {
+            (root_b, root_a)
+        } else {
+            (root_a, root_b)
+        };
+        if self.rank[root] == self.rank[child] {
+            self.rank[root] += 1;
+        }
+        self.parent[child] = root;
+        self.size[root] += self.size[child];
+        self.num_sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Number of elements in the set containing `x`.
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+}
+
+/// Inclusive bounding box of a region, in `(row, col)` grid coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_row: usize,
+    pub min_col: usize,
+    pub max_row: usize,
+    pub max_col: usize,
+}
+
+impl BoundingBox {
+    pub fn height(&self) -> usize {
+        self.max_row - self.min_row + 1
+    }
+
+    pub fn width(&self) -> usize {
+        self.max_col - self.min_col + 1
+    }
+}
+
+/// One connected component of a labelled grid.
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub label: usize,
+    pub area: usize,
+    pub bounds: BoundingBox,
+    /// Cells of the region in row-major order.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Result of [`label_components`]: `labels[r][c]` is the index into `regions`
+/// of the region that owns cell `(r, c)`.
+#[derive(Debug, Clone)]
+pub struct Labelling {
+    pub labels: Vec<Vec<usize>>,
+    pub regions: Vec<Region>,
+}
+
+/// Labels connected components of `grid`.
+/// Two adjacent cells (as defined by `connectivity`) belong to the same region
+/// when `same_region(a, b)` holds; the predicate is expected to be an equivalence.
+/// Labels are numbered in row-major order of each region's first cell.
+pub fn label_components<T, F>(grid: &[Vec<T>], connectivity: Connectivity, same_region: F) -> Labelling
+where
+    F: Fn(&T, &T) -> bool,
+{
+    let rows = grid.len();
+    let cols = grid.first().map_or(0, |row| row.len());
+    let mut sets = DisjointSet::new(rows * cols);
+
+    for r in 0..rows {
+        for c in 0..cols {
+            for &(dr, dc) in connectivity.backward_offsets() {
+                let nr = r as isize + dr;
+                let nc = c as isize + dc;
+                if nr < 0 || nc < 0 || nc >= cols as isize {
+                    continue;
+                }
+                let (nr, nc) = (nr as usize, nc as usize);
+                if same_region(&grid[r][c], &grid[nr][nc]) {
+                    sets.union(r * cols + c, nr * cols + nc);
+                }
+            }
+        }
+    }
+
+    // Map set representatives to dense labels in order of first appearance
+    let mut label_of_root = vec![usize::MAX; rows * cols];
+    let mut labels = vec![vec![0; cols]; rows];
+    let mut regions: Vec<Region> = Vec::with_capacity(sets.num_sets());
+
+    for (r, label_row) in labels.iter_mut().enumerate() {
+        for (c, label) in label_row.iter_mut().enumerate() {
+            let root = sets.find(r * cols + c);
+            if label_of_root[root] == usize::MAX {
+                label_of_root[root] = regions.len();
+                regions.push(Region {
+                    label: regions.len(),
+                    area: 0,
+                    bounds: BoundingBox { min_row: r, min_col: c, max_row: r, max_col: c },
+                    cells: Vec::with_capacity(sets.set_size(root)),
+                });
+            }
+
+            *label = label_of_root[root];
+            let region = &mut regions[*label];
+            region.area += 1;
+            region.bounds.min_col = region.bounds.min_col.min(c);
+            region.bounds.max_col = region.bounds.max_col.max(c);
+            region.bounds.max_row = r;
+            region.cells.push((r, c));
+        }
+    }
+
+    Labelling { labels, regions }
+}
@@ -0,0 +1,326 @@
+// Sorting and counting more values than fit in memory: values are buffered up to a fixed
+// capacity, spilled to temporary files as sorted runs, and read back through a k-way merge.
+// Every structure reports the bytes it holds, so callers can check their memory budget.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::vec;
+
+/// A value with a fixed-size binary encoding of at most 16 bytes, so runs can be written
+/// to and read from files.
+pub trait Record: Copy + Ord {
+    const SIZE: usize;
+
+    fn encode(&self, bytes: &mut [u8]);
+    fn decode(bytes: &[u8]) -> Self;
+}
+
+impl Record for u64 {
+    const SIZE: usize = 8;
+
+    fn encode(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.to_le_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        u64::from_le_bytes(bytes.try_into().unwrap())
+    }
+}
+
+impl Record for (u64, u64) {
+    const SIZE: usize = 16;
+
+    fn encode(&self, bytes: &mut [u8]) {
+        self.0.encode(&mut bytes[..8]);
+        self.1.encode(&mut bytes[8..]);
+    }
+
+    fn decode(bytes: &[u8]) -> Self {
+        (u64::decode(&bytes[..8]), u64::decode(&bytes[8..]))
+    }
+}
+
+/// Largest read buffer per run while merging.
+const MAX_READ_BUFFER: usize = 1 << 16;
+
+/// Most runs merged at once, so the open files stay far below the usual limits.
+const MAX_FAN_IN: usize = 64;
+
+/// A file in the temporary directory that is removed when dropped.
+#[derive(Debug)]
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn create(dir: &Path) -> io::Result<(TempFile, File)> {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("aoc-{}-{}.run", process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+        let path = dir.join(name);
+        let file = File::create(&path).map_err(|err| io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))?;
+        Ok((TempFile { path }, file))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Writes `records`, which must be sorted, as a run in `dir`.
+fn write_run<T: Record>(dir: &Path, records: impl Iterator<Item = io::Result<T>>) -> io::Result<TempFile> {
+    let (run, file) = TempFile::create(dir)?;
+    let mut writer = BufWriter::new(file);
+    let mut bytes = vec![0; T::SIZE];
+    for record in records {
+        record?.encode(&mut bytes);
+        writer.write_all(&bytes)?;
+    }
+    writer.flush()?;
+    Ok(run)
+}
+
+/// One sorted input of a [`Merge`].
+enum Source<T> {
+    Memory(vec::IntoIter<T>),
+    File(BufReader<File>),
+}
+
+impl<T: Record> Source<T> {
+    fn next(&mut self) -> io::Result<Option<T>> {
+        match self {
+            Source::Memory(records) => Ok(records.next()),
+            Source::File(reader) => {
+                if reader.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                let mut bytes = [0; 16];
+                reader.read_exact(&mut bytes[..T::SIZE])?;
+                Ok(Some(T::decode(&bytes[..T::SIZE])))
+            }
+        }
+    }
+
+    fn memory(&self) -> usize {
+        match self {
+            Source::Memory(records) => records.len() * mem::size_of::<T>(),
+            Source::File(reader) => reader.capacity(),
+        }
+    }
+}
+
+/// K-way merge of sorted runs, yielding all their records in order.
+pub struct Merge<T: Record> {
+    sources: Vec<Source<T>>,
+    heap: BinaryHeap<Reverse<(T, usize)>>,
+    // Kept so the files live as long as their readers
+    _runs: Vec<TempFile>,
+}
+
+impl<T: Record> Merge<T> {
+    fn new(mut sources: Vec<Source<T>>, runs: Vec<TempFile>) -> io::Result<Merge<T>> {
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (i, source) in sources.iter_mut().enumerate() {
+            if let Some(record) = source.next()? {
+                heap.push(Reverse((record, i)));
+            }
+        }
+        Ok(Merge { sources, heap, _runs: runs })
+    }
+
+    /// Bytes held by the read buffers, the records still in memory and the heap.
+    pub fn memory(&self) -> usize {
+        self.sources.iter().map(Source::memory).sum::<usize>() + self.heap.capacity() * mem::size_of::<(T, usize)>()
+    }
+}
+
+impl<T: Record> Iterator for Merge<T> {
+    type Item = io::Result<T>;
+
+    fn next(&mut self) -> Option<io::Result<T>> {
+        let Reverse((record, i)) = self.heap.pop()?;
+        match self.sources[i].next() {
+            Ok(Some(next)) => self.heap.push(Reverse((next, i))),
+            Ok(None) => {}
+            Err(err) => return Some(Err(err)),
+        }
+        Some(Ok(record))
+    }
+}
+
+/// Sorts any number of records while holding at most `capacity` of them in memory.
+pub struct ExternalSorter<T: Record> {
+    dir: PathBuf,
+    capacity: usize,
+    buffer: Vec<T>,
+    runs: Vec<TempFile>,
+}
+
+impl<T: Record> ExternalSorter<T> {
+    /// A sorter that spills runs of up to `capacity` records (at least one) into `dir`.
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize) -> ExternalSorter<T> {
+        let capacity = capacity.max(1);
+        ExternalSorter { dir: dir.into(), capacity, buffer: Vec::with_capacity(capacity), runs: Vec::new() }
+    }
+
+    /// A sorter that holds at most `bytes` of records.
+    pub fn with_memory(dir: impl Into<PathBuf>, bytes: usize) -> ExternalSorter<T> {
+        ExternalSorter::new(dir, bytes / mem::size_of::<T>())
+    }
+
+    pub fn push(&mut self, record: T) -> io::Result<()> {
+        if self.buffer.len() == self.capacity {
+            self.spill()?;
+        }
+        self.buffer.push(record);
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        self.buffer.sort_unstable();
+        self.runs.push(write_run(&self.dir, self.buffer.drain(..).map(Ok))?);
+        Ok(())
+    }
+
+    /// Number of runs written to disk so far.
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Bytes held by the buffer.
+    pub fn memory(&self) -> usize {
+        self.buffer.capacity() * mem::size_of::<T>()
+    }
+
+    /// All records pushed, in order.
+    pub fn finish(mut self) -> io::Result<Merge<T>> {
+        let memory = self.memory();
+        let mut records = mem::take(&mut self.buffer);
+        records.sort_unstable();
+        merge_runs(&self.dir, mem::take(&mut self.runs), records, memory)
+    }
+}
+
+/// Merges the spilled `runs` with the sorted `last` run. If anything was spilled, `last`
+/// is spilled too, so that the read buffers can share the whole `memory` budget.
+/// With more than `MAX_FAN_IN` runs, groups of them are first merged into longer runs,
+/// pass after pass, until a single merge can take them all.
+fn merge_runs<T: Record>(dir: &Path, mut runs: Vec<TempFile>, last: Vec<T>, memory: usize) -> io::Result<Merge<T>> {
+    if runs.is_empty() {
+        return Merge::new(vec![Source::Memory(last.into_iter())], runs);
+    }
+    if !last.is_empty() {
+        runs.push(write_run(dir, last.into_iter().map(Ok))?);
+    }
+    while runs.len() > MAX_FAN_IN {
+        let mut merged = Vec::with_capacity(runs.len().div_ceil(MAX_FAN_IN));
+        while !runs.is_empty() {
+            let group: Vec<TempFile> = runs.drain(..runs.len().min(MAX_FAN_IN)).collect();
+            merged.push(write_run(dir, open_runs::<T>(group, memory)?)?);
+        }
+        runs = merged;
+    }
+    open_runs(runs, memory)
+}
+
+/// A merge reading every run in `runs` through buffers that share `memory`.
+fn open_runs<T: Record>(runs: Vec<TempFile>, memory: usize) -> io::Result<Merge<T>> {
+    let read_buffer = (memory / runs.len()).clamp(T::SIZE, MAX_READ_BUFFER);
+    let mut sources = Vec::with_capacity(runs.len());
+    for run in &runs {
+        sources.push(Source::File(BufReader::with_capacity(read_buffer, File::open(&run.path)?)));
+    }
+    Merge::new(sources, runs)
+}
+
+/// Counts how often each value occurs while holding at most `capacity` distinct values
+/// in memory; when the map is full, its counts are spilled as a sorted run.
+pub struct ExternalCounter {
+    dir: PathBuf,
+    capacity: usize,
+    counts: HashMap<u64, u64>,
+    runs: Vec<TempFile>,
+}
+
+impl ExternalCounter {
+    pub fn new(dir: impl Into<PathBuf>, capacity: usize) -> ExternalCounter {
+        let capacity = capacity.max(1);
+        ExternalCounter { dir: dir.into(), capacity, counts: HashMap::with_capacity(capacity), runs: Vec::new() }
+    }
+
+    /// A counter whose map holds at most about `bytes`.
+    pub fn with_memory(dir: impl Into<PathBuf>, bytes: usize) -> ExternalCounter {
+        // A map entry takes its key and value plus one control byte, and the map keeps
+        // an eighth of its slots free
+        ExternalCounter::new(dir, bytes / (mem::size_of::<(u64, u64)>() + 1) * 7 / 8)
+    }
+
+    pub fn add(&mut self, value: u64) -> io::Result<()> {
+        if self.counts.len() == self.capacity && !self.counts.contains_key(&value) {
+            let mut counts: Vec<(u64, u64)> = self.counts.drain().collect();
+            counts.sort_unstable();
+            self.runs.push(write_run(&self.dir, counts.into_iter().map(Ok))?);
+        }
+        *self.counts.entry(value).or_insert(0) += 1;
+        Ok(())
+    }
+
+    /// Number of runs written to disk so far.
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Bytes held by the map.
+    pub fn memory(&self) -> usize {
+        self.counts.capacity() * (mem::size_of::<(u64, u64)>() + 1)
+    }
+
+    /// `(value, count)` for every distinct value, in order of the values.
+    pub fn finish(mut self) -> io::Result<Counts> {
+        let memory = self.memory();
+        let mut counts: Vec<(u64, u64)> = mem::take(&mut self.counts).into_iter().collect();
+        counts.sort_unstable();
+        let mut merge = merge_runs(&self.dir, mem::take(&mut self.runs), counts, memory)?;
+        let next = merge.next().transpose()?;
+        Ok(Counts { merge, next })
+    }
+}
+
+/// The counts of an [`ExternalCounter`], with the partial counts of a value from different runs added up.
+pub struct Counts {
+    merge: Merge<(u64, u64)>,
+    next: Option<(u64, u64)>,
+}
+
+impl Counts {
+    pub fn memory(&self) -> usize {
+        self.merge.memory()
+    }
+}
+
+impl Iterator for Counts {
+    type Item = io::Result<(u64, u64)>;
+
+    fn next(&mut self) -> Option<io::Result<(u64, u64)>> {
+        let (value, mut count) = self.next.take()?;
+        loop {
+            match self.merge.next() {
+                Some(Ok((next_value, next_count))) if next_value == value => count += next_count,
+                Some(Ok(next)) => {
+                    self.next = Some(next);
+                    break;
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => break,
+            }
+        }
+        Some(Ok((value, count)))
+    }
+}
@@ -0,0 +1,139 @@
+// Directed graph with topological sorting and cycle detection,
+// for puzzles where input rules define a partial order ("a must come before b").
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+pub struct DiGraph<N> {
+    successors: HashMap<N, HashSet<N>>,
+}
+
+impl<N> Default for DiGraph<N> {
+    fn default() -> Self {
+        DiGraph { successors: HashMap::new() }
+    }
+}
+
+impl<N: Copy + Eq + Hash> DiGraph<N> {
+    pub fn new() -> DiGraph<N> {
+        DiGraph::default()
+    }
+
+    /// Builds a graph from `(from, to)` edges.
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> DiGraph<N> {
+        let mut graph = DiGraph::new();
+        for (from, to) in edges {
+            graph.add_edge(from, to);
+        }
+        graph
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N) {
+        self.successors.entry(from).or_default().insert(to);
+    }
+
+    pub fn has_edge(&self, from: N, to: N) -> bool {
+        self.successors.get(&from).is_some_and(|succs| succs.contains(&to))
+    }
+
+    pub fn successors(&self, node: N) -> impl Iterator<Item = N> + '_ {
+        self.successors.get(&node).into_iter().flatten().copied()
+    }
+
+    /// Checks that no edge between two nodes of `order` points backwards.
+    /// Edges touching nodes outside of `order` are ignored.
+    pub fn respects_order(&self, order: &[N]) -> bool {
+        let position: HashMap<N, usize> = order.iter().enumerate().map(|(i, &n)| (n, i)).collect();
+        order.iter().enumerate().all(|(i, &node)| {
+            self.successors(node)
+                .filter_map(|succ| position.get(&succ))
+                .all(|&j| j > i)
+        })
+    }
+
+    /// Kahn's algorithm on the subgraph induced by `nodes`.
+    /// Ties are broken by the order of `nodes`, so an already valid order is returned unchanged.
+    /// Fails with the offending cycle if the induced subgraph is not acyclic.
+    pub fn topological_sort(&self, nodes: &[N]) -> Result<Vec<N>, Vec<N>> {
+        // Duplicates in `nodes` keep their first position only
+        let mut subset: HashSet<N> = HashSet::with_capacity(nodes.len());
+        let order: Vec<N> = nodes.iter().copied().filter(|&n| subset.insert(n)).collect();
+        let mut in_degree: HashMap<N, usize> = order.iter().map(|&n| (n, 0)).collect();
+        for &node in &order {
+            for succ in self.successors(node).filter(|succ| subset.contains(succ)) {
+                *in_degree.get_mut(&succ).unwrap() += 1;
+            }
+        }
+
+        let mut queue: VecDeque<N> = order.iter().copied().filter(|n| in_degree[n] == 0).collect();
+        let mut sorted = Vec::with_capacity(order.len());
+
+        while let Some(node) = queue.pop_front() {
+            sorted.push(node);
+            // Visit successors in `nodes` order to keep the result deterministic
+            let succs: HashSet<N> = self.successors(node).filter(|succ| subset.contains(succ)).collect();
+            for &succ in order.iter().filter(|n| succs.contains(n)) {
+                let degree = in_degree.get_mut(&succ).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(succ);
+                }
+            }
+        }
+
+        if sorted.len() == order.len() {
+            Ok(sorted)
+        } else {
+            let emitted: HashSet<N> = sorted.into_iter().collect();
+            let remaining: Vec<N> = order.into_iter().filter(|n| !emitted.contains(n)).collect();
+            Err(self.find_cycle(&remaining).expect("Kahn's algorithm stalled without a cycle"))
+        }
+    }
+
+    /// Depth-first search for a cycle in the subgraph induced by `nodes`.
+    /// The cycle is returned as its nodes in edge order, without repeating the first one.
+    pub fn find_cycle(&self, nodes: &[N]) -> Option<Vec<N>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            OnStack,
+            Done,
+        }
+
+        let subset: HashSet<N> = nodes.iter().copied().collect();
+        let mut marks: HashMap<N, Mark> = HashMap::new();
+
+        for &start in nodes {
+            if marks.contains_key(&start) {
+                continue;
+            }
+            // Iterative DFS; each frame holds the node and its not yet visited successors
+            let mut path: Vec<N> = vec![start];
+            let mut frames: Vec<Vec<N>> = vec![self.successors(start).filter(|s| subset.contains(s)).collect()];
+            marks.insert(start, Mark::OnStack);
+
+            while let Some(frame) = frames.last_mut() {
+                match frame.pop() {
+                    Some(next) => match marks.get(&next) {
+                        Some(Mark::OnStack) => {
+                            let cycle_start = path.iter().position(|&n| n == next).unwrap();
+                            return Some(path[cycle_start..].to_vec());
+                        }
+                        Some(Mark::Done) => {}
+                        None => {
+                            marks.insert(next, Mark::OnStack);
+                            path.push(next);
+                            frames.push(self.successors(next).filter(|s| subset.contains(s)).collect());
+                        }
+                    },
+                    None => {
+                        frames.pop();
+                        let done = path.pop().unwrap();
+                        marks.insert(done, Mark::Done);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,213 @@
+// Image export of grid states: every grid cell becomes a `scale x scale` block of pixels
+// whose colour comes from a palette, with overlay layers painted on top.
+// Encoding is deterministic, so the same grid always produces the same file.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+    pub const RED: Rgb = Rgb(220, 50, 47);
+    pub const GREEN: Rgb = Rgb(133, 153, 0);
+    pub const YELLOW: Rgb = Rgb(181, 137, 0);
+    pub const BLUE: Rgb = Rgb(38, 139, 210);
+
+    /// A distinct, stable colour for every label: hues are spread by the golden angle.
+    pub fn from_label(label: usize) -> Rgb {
+        let hue = (label as f64 * 137.507_764) % 360.0;
+        Rgb::from_hsv(hue, 0.65, 0.9)
+    }
+
+    /// `hue` in degrees, `saturation` and `value` in `0.0..=1.0`.
+    pub fn from_hsv(hue: f64, saturation: f64, value: f64) -> Rgb {
+        let c = value * saturation;
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+        let m = value - c;
+        let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+        Rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// CSS / SVG colour notation, e.g. `#ff8000`.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Linear interpolation from `self` (`t = 0`) to `other` (`t = 1`).
+    pub fn lerp(self, other: Rgb, t: f64) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Rgb(mix(self.0, other.0), mix(self.1, other.1), mix(self.2, other.2))
+    }
+}
+
+/// Maps grid values to colours. Any `Fn(&T) -> Rgb` closure is a palette.
+pub trait Palette<T> {
+    fn color(&self, value: &T) -> Rgb;
+}
+
+impl<T, F: Fn(&T) -> Rgb> Palette<T> for F {
+    fn color(&self, value: &T) -> Rgb {
+        self(value)
+    }
+}
+
+/// Palette for character maps: fixed colours for known characters,
+/// a distinct colour per character for everything else.
+#[derive(Debug, Clone, Default)]
+pub struct CharPalette {
+    colors: HashMap<char, Rgb>,
+}
+
+impl CharPalette {
+    /// `.` as background and `#` as walls, the usual map characters.
+    pub fn new() -> CharPalette {
+        CharPalette::default().with('.', Rgb(24, 24, 24)).with('#', Rgb::GREY)
+    }
+
+    pub fn with(mut self, ch: char, color: Rgb) -> CharPalette {
+        self.colors.insert(ch, color);
+        self
+    }
+}
+
+impl Palette<char> for CharPalette {
+    fn color(&self, value: &char) -> Rgb {
+        self.colors.get(value).copied().unwrap_or_else(|| Rgb::from_label(*value as usize))
+    }
+}
+
+/// Palette for height maps: a gradient from `low` at `min` to `high` at `max`.
+/// Values outside of `min..=max` (such as impassable tiles) are drawn in `outside`.
+#[derive(Debug, Clone, Copy)]
+pub struct HeightPalette {
+    pub min: i32,
+    pub max: i32,
+    pub low: Rgb,
+    pub high: Rgb,
+    pub outside: Rgb,
+}
+
+impl Palette<i32> for HeightPalette {
+    fn color(&self, value: &i32) -> Rgb {
+        if *value < self.min || *value > self.max {
+            return self.outside;
+        }
+        let span = (self.max - self.min).max(1) as f64;
+        self.low.lerp(self.high, (*value - self.min) as f64 / span)
+    }
+}
+
+/// One colour per grid cell, scaled up to pixels when written.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Image {
+    rows: usize,
+    cols: usize,
+    cells: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn from_grid<T>(grid: &[Vec<T>], palette: &impl Palette<T>) -> Image {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        let cells = grid.iter().flat_map(|row| row.iter().map(|value| palette.color(value))).collect();
+        Image { rows, cols, cells }
+    }
+
+    pub fn filled(rows: usize, cols: usize, color: Rgb) -> Image {
+        Image { rows, cols, cells: vec![color; rows * cols] }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Rgb {
+        self.cells[row * self.cols + col]
+    }
+
+    /// Paints the cells in `color`. Cells outside of the image are ignored.
+    pub fn overlay<I>(&mut self, cells: I, color: Rgb) -> &mut Image
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        self.tint(cells, color, 1.0)
+    }
+
+    /// Blends `color` into the cells with the given opacity (`0.0..=1.0`).
+    pub fn tint<I>(&mut self, cells: I, color: Rgb, opacity: f64) -> &mut Image
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        for (row, col) in cells {
+            if row < self.rows && col < self.cols {
+                let cell = &mut self.cells[row * self.cols + col];
+                *cell = cell.lerp(color, opacity);
+            }
+        }
+        self
+    }
+
+    /// Colours every cell by its region label.
+    pub fn color_regions(&mut self, labels: &[Vec<usize>]) -> &mut Image {
+        for (row, label_row) in labels.iter().enumerate() {
+            for (col, &label) in label_row.iter().enumerate() {
+                self.overlay([(row, col)], Rgb::from_label(label));
+            }
+        }
+        self
+    }
+
+    /// Raw RGB8 pixels, row by row, with every cell drawn as a `scale x scale` block.
+    pub fn pixels(&self, scale: u32) -> Vec<u8> {
+        let scale = scale.max(1) as usize;
+        let mut pixels = Vec::with_capacity(self.cells.len() * scale * scale * 3);
+        for row in self.cells.chunks(self.cols.max(1)) {
+            let mut line = Vec::with_capacity(self.cols * scale * 3);
+            for &Rgb(r, g, b) in row {
+                for _ in 0..scale {
+                    line.extend_from_slice(&[r, g, b]);
+                }
+            }
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        pixels
+    }
+
+    /// Encodes the image as a PNG with every cell `scale` pixels wide.
+    pub fn encode_png<W: Write>(&self, writer: W, scale: u32) -> io::Result<()> {
+        let scale = scale.max(1);
+        let mut encoder = png::Encoder::new(writer, self.cols as u32 * scale, self.rows as u32 * scale);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png_writer = encoder.write_header().map_err(io::Error::other)?;
+        png_writer.write_image_data(&self.pixels(scale)).map_err(io::Error::other)?;
+        png_writer.finish().map_err(io::Error::other)
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P, scale: u32) -> io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        self.encode_png(file, scale)
+    }
+}
@@ -0,0 +1,31 @@
+pub mod aho_corasick;
+pub mod bitgrid;
+pub mod crosscheck;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod days;
+pub mod digits;
+pub mod disjoint_set;
+pub mod external_sort;
+pub mod graph;
+pub mod image;
+pub mod math;
+pub mod parse;
+pub mod random;
+pub mod recorder;
+pub mod render;
+pub mod simulation;
+pub mod spans;
+pub mod svg;
+pub mod tui;
+pub mod viewer;
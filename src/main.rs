@@ -1,243 +1,319 @@
-use std::collections::{HashMap, HashSet};
-
-/// --- Day 12: Garden Groups ---
-
-/// Depth-first search to find connected components (regions) of the same plant type.
-fn dfs(
-    garden: &Vec<Vec<char>>,
-    visited: &mut Vec<Vec<bool>>,
-    position: (usize, usize),
-) -> Vec<(usize, usize)> {
-    let (start_i, start_j) = position;
-    let mut stack = vec![(start_i, start_j)];
-    let mut component = vec![];
-
-    while let Some((ci, cj)) = stack.pop() {
-        if visited[ci][cj] {
-            continue;
-        }
-        visited[ci][cj] = true;
-        component.push((ci, cj));
-
-        // Check neighbors (up, down, left, right)
-        let neighbors = [
-            (ci.wrapping_sub(1), cj),
-            (ci + 1, cj),
-            (ci, cj.wrapping_sub(1)),
-            (ci, cj + 1),
-        ];
-
-        for &(ni, nj) in &neighbors {
-            if ni < garden.len()
-                && nj < garden[0].len()
-                && !visited[ni][nj]
-                && garden[ni][nj] == garden[ci][cj]
-            {
-                stack.push((ni, nj));
+// `aoc` command line: runs days and opens the interactive explorer.
+//
+//   aoc run [day] [part]          print answers with timings (all days if no day is given)
+//   aoc tui                       browse days, run parts and step through simulations
+//   aoc show <day> [--step N]     print the day's state after N steps (0 by default),
+//                                 in colour and cropped to the terminal around the focus
+//   aoc crosscheck [day] [--seed N] [--runs N] [--size N]
+//                                 compare the implementations of every part on the real
+//                                 input and on random ones
+//   aoc gen <day> [--seed N] [--size N]
+//                                 print a random puzzle input for the day
+//   aoc stream <day> [file] [--memory MiB] [--temp DIR]
+//                                 solve a day reading its input as a stream, from stdin
+//                                 if the file is missing or `-`, optionally spilling to
+//                                 temporary files to stay within a memory limit
+//   aoc png <day> <out.png> [--scale N]
+//                                 save a picture of the day solved for its input,
+//                                 N pixels per cell (4 by default)
+//   aoc svg <day> <out.svg>       save a vector picture of the day solved for its input
+//   aoc record <day> <out.gif|dir> [--every N] [--max-frames N] [--scale N]
+//                                 record the day's simulation as a GIF, or as numbered
+//                                 PNG frames in a directory, keeping every N-th step
+//   aoc compare [file] [--metric NAME]
+//                                 compare the columns of Day 1 style location lists
+//                                 pairwise, with one metric or all of them
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Instant;
+
+use aoc2024::crosscheck::{self, Options};
+use aoc2024::day01::{LocationColumns, Metric};
+use aoc2024::days::{self, Day, Outcome, StreamOptions, DAYS};
+use aoc2024::random::Rng;
+use aoc2024::recorder::Recorder;
+use aoc2024::render::RenderOptions;
+use aoc2024::tui;
+
+const USAGE: &str = "usage: aoc run [day] [part]
+       aoc tui
+       aoc show <day> [--step N]
+       aoc crosscheck [day] [--seed N] [--runs N] [--size N]
+       aoc gen <day> [--seed N] [--size N]
+       aoc stream <day> [file] [--memory MiB] [--temp DIR]
+       aoc png <day> <out.png> [--scale N]
+       aoc svg <day> <out.svg>
+       aoc record <day> <out.gif|dir> [--every N] [--max-frames N] [--scale N]
+       aoc compare [file] [--metric NAME]";
+
+/// Positional arguments and `--name value` flags.
+struct Args {
+    positional: Vec<String>,
+    flags: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Args, String> {
+        let mut parsed = Args { positional: Vec::new(), flags: Vec::new() };
+        let mut args = args.peekable();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args.next().ok_or_else(|| format!("missing value for --{}", name))?;
+                    parsed.flags.push((name.to_string(), value));
+                }
+                None => parsed.positional.push(arg),
             }
         }
+        Ok(parsed)
     }
 
-    component
-}
+    fn positional<T: FromStr>(&self, index: usize, what: &str) -> Result<Option<T>, String> {
+        self.positional
+            .get(index)
+            .map(|arg| arg.parse().map_err(|_| format!("invalid {}: {}", what, arg)))
+            .transpose()
+    }
 
-// ---------------------------------------------------------------------------
-// PART ONE: Perimeter-Based Fence Price
-// ---------------------------------------------------------------------------
-
-/// Computes the fence price for a region by using `area * perimeter`.
-/// The perimeter is counted as the total number of edges that border
-/// out-of-bounds or a different region type.
-fn compute_perimeter_price(region: &Vec<(usize, usize)>, garden: &Vec<Vec<char>>) -> i32 {
-    let area = region.len(); // number of cells
-    let mut perimeter = 0;
-
-    let rows = garden.len();
-    let cols = garden[0].len();
-
-    // Put all cells of this region into a HashSet for quick lookup
-    let region_set: HashSet<(usize, usize)> = region.iter().copied().collect();
-
-    // For each cell in the region, check its 4 neighbors.
-    // If the neighbor is out of bounds or not in the same region, perimeter++
-    for &(r, c) in &region_set {
-        for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-            let nr = r as isize + dr;
-            let nc = c as isize + dc;
-
-            // Out of bounds => perimeter edge
-            if nr < 0 || nc < 0 || nr >= rows as isize || nc >= cols as isize {
-                perimeter += 1;
-            }
-            // Different region => perimeter edge
-            else if !region_set.contains(&(nr as usize, nc as usize)) {
-                perimeter += 1;
-            }
-        }
+    fn flag<T: FromStr>(&self, name: &str, default: T) -> Result<T, String> {
+        Ok(self.optional_flag(name)?.unwrap_or(default))
+    }
+
+    /// The last value given for `--name`, if any.
+    fn optional_flag<T: FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.flags
+            .iter()
+            .rev()
+            .find(|(flag, _)| flag == name)
+            .map(|(_, value)| value.parse().map_err(|_| format!("invalid --{}: {}", name, value)))
+            .transpose()
+    }
+
+    /// The day given at `index`, which is required.
+    fn day(&self, index: usize) -> Result<&'static Day, String> {
+        let number = self.positional::<u32>(index, "day")?.ok_or_else(|| USAGE.to_string())?;
+        days::find(number).ok_or_else(|| format!("day {} is not implemented", number))
     }
 
-    (area * perimeter) as i32
+    /// The day given at `index`, or every day.
+    fn days(&self, index: usize) -> Result<Vec<Day>, String> {
+        match self.positional::<u32>(index, "day")? {
+            None => Ok(DAYS.to_vec()),
+            Some(number) => match days::find(number) {
+                Some(day) => Ok(vec![*day]),
+                None => Err(format!("day {} is not implemented", number)),
+            },
+        }
+    }
 }
 
-/// Finds connected components (regions) in `garden` and computes the total fence
-/// price using the perimeter-based cost.
-fn part_one(garden: &Vec<Vec<char>>) {
-    let rows = garden.len();
-    let cols = garden[0].len();
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut components = Vec::new();
-
-    // Identify all connected regions via DFS
-    for i in 0..rows {
-        for j in 0..cols {
-            if !visited[i][j] {
-                let component = dfs(garden, &mut visited, (i, j));
-                components.push(component);
+fn run(args: &Args) -> Result<ExitCode, String> {
+    let part = args.positional::<u32>(2, "part")?;
+    let mut status = ExitCode::SUCCESS;
+    for day in args.days(1)? {
+        let parts: Vec<u32> = match part {
+            Some(part) => vec![part],
+            None => (1..=2).filter(|&part| day.part(part).is_some()).collect(),
+        };
+        for part in parts {
+            match days::run_part(&day, part) {
+                Outcome::Answer(answer, elapsed) => {
+                    println!("Day {:>2} part {}: {:<20} ({:.2?})", day.number, part, answer, elapsed)
+                }
+                Outcome::Failed(message) => {
+                    eprintln!("Day {:>2} part {}: {}", day.number, part, message);
+                    status = ExitCode::FAILURE;
+                }
             }
         }
     }
-
-    // Sum perimeter-based fence prices
-    let total_price: i32 = components
-        .iter()
-        .map(|region| compute_perimeter_price(region, garden))
-        .sum();
-
-    println!("Part one: {}", total_price);
+    Ok(status)
 }
 
-// ---------------------------------------------------------------------------
-// PART TWO: Bulk Discount Fence Price
-// ---------------------------------------------------------------------------
-
-/// Counts how many "horizontal runs" exist among the given cells.
-/// Each consecutive run of columns in the same row => 1 side.
-fn count_horizontal_runs(cells: &[(usize, usize)]) -> usize {
-    let mut rows_map: HashMap<usize, Vec<usize>> = HashMap::new();
-    for &(r, c) in cells {
-        rows_map.entry(r).or_default().push(c);
+fn show(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let open_view = day.view.ok_or_else(|| format!("day {} has nothing to show", day.number))?;
+    let mut viewer = open_view(&read_input(day)?).ok_or_else(|| format!("day {} has no states", day.number))?;
+    let step = args.flag("step", 0)?;
+    if viewer.go_to(step) < step {
+        eprintln!("Day {:>2}: the last state is at step {}", day.number, viewer.steps());
     }
 
-    let mut total = 0;
-    for (_row, mut cols) in rows_map {
-        cols.sort_unstable();
-        total += count_runs_in_sorted_list(&cols);
-    }
-    total
+    let snapshot = viewer.snapshot();
+    let options = match snapshot.focus {
+        Some((row, col)) => RenderOptions::default().centered_on(row, col),
+        None => RenderOptions::default(),
+    };
+    snapshot.frame.print(&options);
+    println!("{}", snapshot.status);
+    Ok(ExitCode::SUCCESS)
 }
 
-/// Counts how many "vertical runs" exist among the given cells.
-/// Each consecutive run of rows in the same column => 1 side.
-fn count_vertical_runs(cells: &[(usize, usize)]) -> usize {
-    let mut cols_map: HashMap<usize, Vec<usize>> = HashMap::new();
-    for &(r, c) in cells {
-        cols_map.entry(c).or_default().push(r);
+fn crosscheck(args: &Args) -> Result<ExitCode, String> {
+    let defaults = Options::default();
+    let options = Options {
+        seed: args.flag("seed", defaults.seed)?,
+        runs: args.flag("runs", defaults.runs)?,
+        max_size: args.flag("size", defaults.max_size)?,
+        real_input: defaults.real_input,
+    };
+
+    let mut status = ExitCode::SUCCESS;
+    for day in args.days(1)? {
+        for part in 1..=2 {
+            let Some(report) = crosscheck::crosscheck(&day, part, &options) else {
+                continue;
+            };
+            let names = report.implementations.join(", ");
+            let Some(disagreement) = report.disagreement else {
+                println!("Day {:>2} part {}: {} agree on {} inputs", day.number, part, names, report.inputs);
+                continue;
+            };
+
+            status = ExitCode::FAILURE;
+            println!("Day {:>2} part {}: {} disagree on {}", day.number, part, names, disagreement.source);
+            println!("  smallest input found ({} lines):", disagreement.input.lines().count());
+            for line in disagreement.input.lines() {
+                println!("    {}", line);
+            }
+            for (name, result) in disagreement.results {
+                match result {
+                    Ok(answer) => println!("  {}: {}", name, answer),
+                    Err(message) => println!("  {}: {}", name, message),
+                }
+            }
+        }
     }
+    Ok(status)
+}
 
-    let mut total = 0;
-    for (_col, mut rows) in cols_map {
-        rows.sort_unstable();
-        total += count_runs_in_sorted_list(&rows);
-    }
-    total
+fn generate(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let generate = day.generate.ok_or_else(|| format!("day {} has no input generator", day.number))?;
+    let mut rng = Rng::new(args.flag("seed", 1)?);
+    print!("{}", generate(&mut rng, args.flag("size", 20)?));
+    Ok(ExitCode::SUCCESS)
 }
 
-/// Counts consecutive-value runs in a sorted list.
-/// E.g. [2,3,4,7,8,10] => 3 runs: [2..=4], [7..=8], [10..=10].
-fn count_runs_in_sorted_list(sorted_vals: &[usize]) -> usize {
-    if sorted_vals.is_empty() {
-        return 0;
+/// Opens the file given at `index` for reading, or stdin if it is missing or `-`.
+/// Returns the reader with a name for error messages.
+fn open_input(args: &Args, index: usize) -> Result<(String, Box<dyn BufRead>), String> {
+    match args.positional::<PathBuf>(index, "file")?.filter(|path| path.as_os_str() != "-") {
+        Some(path) => {
+            let file = File::open(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            Ok((path.display().to_string(), Box::new(BufReader::new(file))))
+        }
+        None => Ok(("stdin".to_string(), Box::new(io::stdin().lock()))),
     }
+}
 
-    let mut runs = 1;
-    let mut last = sorted_vals[0];
-    for &v in &sorted_vals[1..] {
-        if v != last + 1 {
-            runs += 1;
-        }
-        last = v;
+fn stream(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let solve = day.stream.ok_or_else(|| format!("day {} cannot read a stream", day.number))?;
+    let defaults = StreamOptions::default();
+    let options = StreamOptions {
+        memory: args.optional_flag::<usize>("memory")?.map(|mebibytes| mebibytes << 20),
+        temp_dir: args.flag("temp", defaults.temp_dir)?,
+    };
+
+    let start = Instant::now();
+    let (name, mut reader) = open_input(args, 2)?;
+    let streamed = solve(&mut reader, &options).map_err(|err| format!("{}: {}", name, err))?;
+    let elapsed = start.elapsed();
+    for (part, answer) in streamed.answers.iter().enumerate() {
+        println!("Day {:>2} part {}: {}", day.number, part + 1, answer);
+    }
+    for detail in streamed.details {
+        println!("{}", detail);
     }
-    runs
+    println!("({:.2?})", elapsed);
+    Ok(ExitCode::SUCCESS)
 }
 
-/// Computes the fence price using the bulk-discount rule: `area * number_of_sides`.
-/// Here, each continuous straight line (top, bottom, left, right) is counted as 1 side.
-fn compute_bulk_fence_price(region: &[(usize, usize)], garden: &Vec<Vec<char>>) -> i32 {
-    let area = region.len();
-    let rows = garden.len();
-    let cols = garden[0].len();
-
-    // Identify which cells have top/bottom/left/right edges
-    let top_edge_cells: Vec<(usize, usize)> = region
-        .iter()
-        .copied()
-        .filter(|&(r, c)| r == 0 || garden[r - 1][c] != garden[r][c])
-        .collect();
-
-    let bottom_edge_cells: Vec<(usize, usize)> = region
-        .iter()
-        .copied()
-        .filter(|&(r, c)| r == rows - 1 || garden[r + 1][c] != garden[r][c])
-        .collect();
-
-    let left_edge_cells: Vec<(usize, usize)> = region
-        .iter()
-        .copied()
-        .filter(|&(r, c)| c == 0 || garden[r][c - 1] != garden[r][c])
-        .collect();
-
-    let right_edge_cells: Vec<(usize, usize)> = region
-        .iter()
-        .copied()
-        .filter(|&(r, c)| c == cols - 1 || garden[r][c + 1] != garden[r][c])
-        .collect();
-
-    // Count straight runs in horizontal and vertical directions
-    let top_sides    = count_horizontal_runs(&top_edge_cells);
-    let bottom_sides = count_horizontal_runs(&bottom_edge_cells);
-    let left_sides   = count_vertical_runs(&left_edge_cells);
-    let right_sides  = count_vertical_runs(&right_edge_cells);
-
-    let total_sides = top_sides + bottom_sides + left_sides + right_sides;
-    // println!("Bulk region '{}' => area={} sides={}", garden[region[0].0][region[0].1], area, total_sides);
-
-    (area * total_sides) as i32
+/// The day's real input, for the commands that draw it.
+fn read_input(day: &Day) -> Result<String, String> {
+    day.input().map_err(|err| format!("{}: {}", day.input_path().display(), err))
 }
 
-/// Finds connected components (regions) in `garden` and computes the total fence
-/// price using the bulk discount cost.
-fn part_two(garden: &Vec<Vec<char>>) {
-    let rows = garden.len();
-    let cols = garden[0].len();
-    let mut visited = vec![vec![false; cols]; rows];
-    let mut components = Vec::new();
-
-    // Identify all connected regions via DFS
-    for i in 0..rows {
-        for j in 0..cols {
-            if !visited[i][j] {
-                let component = dfs(garden, &mut visited, (i, j));
-                components.push(component);
-            }
-        }
-    }
+fn png(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let save = day.png.ok_or_else(|| format!("day {} has no picture", day.number))?;
+    let path = args.positional::<PathBuf>(2, "output")?.ok_or_else(|| USAGE.to_string())?;
+    save(&read_input(day)?, &path, args.flag("scale", 4)?).map_err(|err| format!("{}: {}", path.display(), err))?;
+    println!("Day {:>2}: wrote {}", day.number, path.display());
+    Ok(ExitCode::SUCCESS)
+}
 
-    // Sum bulk-discount fence prices
-    let total_price: i32 = components
-        .iter()
-        .map(|region| compute_bulk_fence_price(region, garden))
-        .sum();
+fn svg(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let save = day.svg.ok_or_else(|| format!("day {} has no vector picture", day.number))?;
+    let path = args.positional::<PathBuf>(2, "output")?.ok_or_else(|| USAGE.to_string())?;
+    save(&read_input(day)?, &path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    println!("Day {:>2}: wrote {}", day.number, path.display());
+    Ok(ExitCode::SUCCESS)
+}
 
-    println!("Part two: {}", total_price);
+fn record(args: &Args) -> Result<ExitCode, String> {
+    let day = args.day(1)?;
+    let record = day.record.ok_or_else(|| format!("day {} has no simulation to record", day.number))?;
+    let path = args.positional::<PathBuf>(2, "output")?.ok_or_else(|| USAGE.to_string())?;
+    let recorder = match path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gif")) {
+        true => Recorder::gif(&path),
+        false => Recorder::png_frames(&path),
+    };
+    let mut recorder = recorder
+        .every(args.flag("every", 1)?)
+        .max_frames(args.flag("max-frames", 1000)?)
+        .scale(args.flag("scale", 4)?);
+
+    let input = read_input(day)?;
+    record(&input, &mut recorder)
+        .and_then(|_| recorder.finish())
+        .map(|frames| println!("Day {:>2}: wrote {} frames to {}", day.number, frames, path.display()))
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(ExitCode::SUCCESS)
 }
 
-fn main() {
-    // Parse the input map
-    let garden: Vec<Vec<char>> = include_str!("../input/12.in")
-        .lines()
-        .map(|line| line.chars().collect())
-        .collect();
+fn compare(args: &Args) -> Result<ExitCode, String> {
+    let metrics = match args.optional_flag::<String>("metric")? {
+        Some(name) => vec![name.parse::<Metric>()?],
+        None => Metric::ALL.to_vec(),
+    };
+    let (name, reader) = open_input(args, 1)?;
+    let columns = LocationColumns::read(reader).map_err(|err| format!("{}: {}", name, err))?;
+
+    for metric in metrics {
+        println!("{}:", metric.name());
+        for row in columns.compare(metric) {
+            let cells: Vec<String> = row.iter().map(|value| format!("{:>14.4}", value)).collect();
+            println!("{}", cells.join(""));
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    part_one(&garden); // perimeter-based price
-    part_two(&garden); // bulk-discount price
-}
\ No newline at end of file
+fn main() -> ExitCode {
+    let result = Args::parse(env::args().skip(1)).and_then(|args| match args.positional.first().map(String::as_str) {
+        Some("run") => run(&args),
+        Some("tui") => tui::run().map(|_| ExitCode::SUCCESS).map_err(|err| err.to_string()),
+        Some("show") => show(&args),
+        Some("crosscheck") => crosscheck(&args),
+        Some("gen") => generate(&args),
+        Some("stream") => stream(&args),
+        Some("png") => png(&args),
+        Some("svg") => svg(&args),
+        Some("record") => record(&args),
+        Some("compare") => compare(&args),
+        _ => Err(USAGE.to_string()),
+    });
+
+    result.unwrap_or_else(|message| {
+        eprintln!("{}", message);
+        ExitCode::FAILURE
+    })
+}
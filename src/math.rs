@@ -0,0 +1,195 @@
+// Number theory over `i64` and `i128`: gcd/lcm, extended Euclid, modular inverse,
+// Chinese remainder theorem and exact solving of 2x2 integer linear systems.
+//
+// Everything that can overflow is checked and reports it as `None`.
+
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn rem_euclid(self, modulus: Self) -> Self;
+
+    /// `(self * other) mod modulus` without overflowing, for `0 <= self, other < modulus`.
+    fn mul_mod(self, other: Self, modulus: Self) -> Self;
+}
+
+impl Integer for i64 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    fn abs(self) -> Self {
+        i64::abs(self)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i64::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i64::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i64::checked_mul(self, other)
+    }
+
+    fn rem_euclid(self, modulus: Self) -> Self {
+        i64::rem_euclid(self, modulus)
+    }
+
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        (self as i128 * other as i128).rem_euclid(modulus as i128) as i64
+    }
+}
+
+impl Integer for i128 {
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+
+    fn abs(self) -> Self {
+        i128::abs(self)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        i128::checked_add(self, other)
+    }
+
+    fn checked_sub(self, other: Self) -> Option<Self> {
+        i128::checked_sub(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        i128::checked_mul(self, other)
+    }
+
+    fn rem_euclid(self, modulus: Self) -> Self {
+        i128::rem_euclid(self, modulus)
+    }
+
+    fn mul_mod(self, other: Self, modulus: Self) -> Self {
+        if let Some(product) = self.checked_mul(other) {
+            return product.rem_euclid(modulus);
+        }
+        // No wider type to fall back to: double-and-add, using unsigned sums so they cannot overflow
+        let modulus = modulus as u128;
+        let (mut a, mut b, mut result) = (self as u128, other as u128, 0u128);
+        while b > 0 {
+            if b & 1 == 1 {
+                result = (result + a) % modulus;
+            }
+            a = (a + a) % modulus;
+            b >>= 1;
+        }
+        result as i128
+    }
+}
+
+/// Greatest common divisor, always non-negative. `gcd(0, 0) = 0`.
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != T::ZERO {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, always non-negative, or `None` on overflow. `lcm(0, x) = 0`.
+pub fn lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    (a / gcd(a, b)).checked_mul(b).map(T::abs)
+}
+
+/// Extended Euclid: returns `(g, x, y)` with `a * x + b * y = g = gcd(a, b)`.
+pub fn egcd<T: Integer>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// Inverse of `a` modulo `m` in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inv<T: Integer>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (g, x, _) = egcd(a.rem_euclid(m), m);
+    if g != T::ONE {
+        return None;
+    }
+    Some(x.rem_euclid(m))
+}
+
+/// Chinese remainder theorem for `x = r_i (mod m_i)`.
+/// The moduli need not be coprime. Returns `(x, lcm of the moduli)` with `x` in `0..lcm`,
+/// or `None` if the congruences contradict each other or the lcm overflows.
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut result = (T::ZERO, T::ONE);
+    for &(r2, m2) in congruences {
+        if m2 <= T::ZERO {
+            return None;
+        }
+        let (r1, m1) = result;
+        let r2 = r2.rem_euclid(m2);
+        let g = gcd(m1, m2);
+        if (r2 - r1).rem_euclid(g) != T::ZERO {
+            return None;
+        }
+
+        // x = r1 + m1 * k, with k = (r2 - r1) / g * inv(m1 / g) mod (m2 / g)
+        let m2_g = m2 / g;
+        let modulus = lcm(m1, m2)?;
+        let k = if m2_g == T::ONE {
+            T::ZERO
+        } else {
+            let inv = mod_inv(m1 / g, m2_g)?;
+            ((r2 - r1) / g).rem_euclid(m2_g).mul_mod(inv, m2_g)
+        };
+        let x = r1.checked_add(m1.mul_mod(k, modulus))?.rem_euclid(modulus);
+        result = (x, modulus);
+    }
+    Some(result)
+}
+
+/// Solves `a * (x, y) = b` exactly over the integers with Cramer's rule.
+/// Returns `None` if the matrix is singular, the solution is not integral or anything overflows.
+pub fn solve_2x2<T: Integer>(a: [[T; 2]; 2], b: [T; 2]) -> Option<(T, T)> {
+    let det = a[0][0].checked_mul(a[1][1])?.checked_sub(a[0][1].checked_mul(a[1][0])?)?;
+    if det == T::ZERO {
+        return None;
+    }
+    let det_x = b[0].checked_mul(a[1][1])?.checked_sub(a[0][1].checked_mul(b[1])?)?;
+    let det_y = a[0][0].checked_mul(b[1])?.checked_sub(b[0].checked_mul(a[1][0])?)?;
+    if det_x % det != T::ZERO || det_y % det != T::ZERO {
+        return None;
+    }
+    Some((det_x / det, det_y / det))
+}
@@ -0,0 +1,183 @@
+// Parsing helpers for the input shapes that keep coming back in the puzzles:
+// numbers scattered in a line, blank-line separated sections, character grids,
+// `key: value` lines and digit strings.
+//
+// Everything tolerates CRLF line endings and trailing whitespace, and failures
+// report the 1-based line and column of the offending character.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Builds an error pointing at byte `offset` of `text`.
+    fn at(text: &str, offset: usize, message: String) -> ParseError {
+        let before = &text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        ParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message,
+        }
+    }
+
+    /// Relocates an error found in a slice of the input that starts on line `first_line`
+    /// of the whole input, e.g. a single line or a [`Section`].
+    pub fn below(mut self, first_line: usize) -> ParseError {
+        self.line += first_line - 1;
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Lines of `input` with line endings (`\n` or `\r\n`) and trailing whitespace removed.
+pub fn lines(input: &str) -> impl Iterator<Item = &str> {
+    input.lines().map(str::trim_end)
+}
+
+/// A block of consecutive non-blank lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// 1-based line number of the first line of the section in the whole input.
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> {
+        lines(self.text)
+    }
+}
+
+/// Splits `input` into sections separated by one or more blank lines.
+pub fn sections(input: &str) -> Vec<Section<'_>> {
+    let mut result = Vec::new();
+    let mut start: Option<(usize, usize)> = None; // (line number, byte offset)
+    let mut offset = 0;
+
+    for (i, raw_line) in input.split_inclusive('\n').enumerate() {
+        let is_blank = raw_line.trim().is_empty();
+        match (start, is_blank) {
+            (None, false) => start = Some((i + 1, offset)),
+            (Some((first_line, from)), true) => {
+                result.push(Section { first_line, text: input[from..offset].trim_end() });
+                start = None;
+            }
+            _ => {}
+        }
+        offset += raw_line.len();
+    }
+    if let Some((first_line, from)) = start {
+        result.push(Section { first_line, text: input[from..].trim_end() });
+    }
+    result
+}
+
+/// Extracts every integer in `text`, ignoring whatever separates them.
+/// A `-` directly in front of a number is read as its sign if `T` is signed
+/// and the `-` does not follow another digit, so `x=-3` gives `-3` but `1-3` gives `1, 3`.
+/// Such a sign is an error if `T` is unsigned, rather than silently dropped.
+pub fn ints<T: FromStr>(text: &str) -> Result<Vec<T>, ParseError> {
+    let is_signed = "-1".parse::<T>().is_ok();
+    let bytes = text.as_bytes();
+    let mut numbers = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() {
+            i += 1;
+            continue;
+        }
+        let mut start = i;
+        if start > 0 && bytes[start - 1] == b'-' && (start < 2 || !bytes[start - 2].is_ascii_digit()) {
+            start -= 1;
+            if !is_signed {
+                let message = format!("negative number where {} was expected", std::any::type_name::<T>());
+                return Err(ParseError::at(text, start, message));
+            }
+        }
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        let number = text[start..i].parse::<T>().map_err(|_| {
+            let message = format!("number `{}` does not fit in {}", &text[start..i], std::any::type_name::<T>());
+            ParseError::at(text, start, message)
+        })?;
+        numbers.push(number);
+    }
+    Ok(numbers)
+}
+
+/// Reads `text` as a string of decimal digits, skipping whitespace.
+pub fn digits(text: &str) -> Result<Vec<u8>, ParseError> {
+    text.char_indices()
+        .filter(|(_, ch)| !ch.is_whitespace())
+        .map(|(offset, ch)| {
+            ch.to_digit(10)
+                .map(|d| d as u8)
+                .ok_or_else(|| ParseError::at(text, offset, format!("expected a digit, found `{}`", ch)))
+        })
+        .collect()
+}
+
+/// Parses a rectangular grid, mapping every character through `mapper`.
+/// Characters the mapper rejects with `None` and rows of differing width are errors.
+/// Trailing blank lines are ignored.
+pub fn grid_of<T, F>(input: &str, mut mapper: F) -> Result<Vec<Vec<T>>, ParseError>
+where
+    F: FnMut(char) -> Option<T>,
+{
+    let rows: Vec<&str> = lines(input).collect();
+    let height = rows.iter().rposition(|row| !row.is_empty()).map_or(0, |last| last + 1);
+    let mut grid: Vec<Vec<T>> = Vec::with_capacity(height);
+
+    for (i, row) in rows[..height].iter().enumerate() {
+        let mut grid_row = Vec::with_capacity(row.len());
+        for (j, ch) in row.chars().enumerate() {
+            match mapper(ch) {
+                Some(value) => grid_row.push(value),
+                None => {
+                    return Err(ParseError { line: i + 1, column: j + 1, message: format!("unexpected `{}` in grid", ch) })
+                }
+            }
+        }
+        if let Some(first_row) = grid.first() {
+            if grid_row.len() != first_row.len() {
+                let message = format!("row has width {}, expected {}", grid_row.len(), first_row.len());
+                return Err(ParseError { line: i + 1, column: 1, message });
+            }
+        }
+        grid.push(grid_row);
+    }
+    Ok(grid)
+}
+
+/// Splits every non-blank line at the first `separator` into a trimmed `(key, value)` pair,
+/// e.g. `190: 10 19` with `':'` gives `("190", "10 19")`.
+pub fn key_values(input: &str, separator: char) -> Result<Vec<(&str, &str)>, ParseError> {
+    lines(input)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| match line.split_once(separator) {
+            Some((key, value)) => Ok((key.trim(), value.trim())),
+            None => Err(ParseError {
+                line: i + 1,
+                column: line.chars().count() + 1,
+                message: format!("missing `{}` separator", separator),
+            }),
+        })
+        .collect()
+}
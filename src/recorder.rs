@@ -0,0 +1,133 @@
+// Recording of step-by-step simulations as an animated GIF or as numbered PNG frames.
+//
+// Simulations call `Recorder::record` once per step with a closure that draws the
+// current state; the closure only runs for the steps that end up in the recording,
+// so a disabled recorder or heavy frame skipping costs next to nothing.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::image::Image;
+
+enum Output {
+    Disabled,
+    Gif {
+        path: PathBuf,
+        encoder: Option<gif::Encoder<BufWriter<File>>>,
+    },
+    PngFrames {
+        dir: PathBuf,
+    },
+}
+
+pub struct Recorder {
+    output: Output,
+    every: usize,
+    max_frames: usize,
+    scale: u32,
+    delay: u16,
+    steps: usize,
+    frames: usize,
+}
+
+impl Recorder {
+    fn with_output(output: Output) -> Recorder {
+        Recorder { output, every: 1, max_frames: 1000, scale: 4, delay: 5, steps: 0, frames: 0 }
+    }
+
+    /// A recorder that ignores every step.
+    pub fn disabled() -> Recorder {
+        Recorder::with_output(Output::Disabled)
+    }
+
+    /// Records into an animated GIF at `path`, written as frames come in.
+    pub fn gif<P: Into<PathBuf>>(path: P) -> Recorder {
+        Recorder::with_output(Output::Gif { path: path.into(), encoder: None })
+    }
+
+    /// Records every frame as `frame_00000.png`, `frame_00001.png`, ... into `dir`.
+    pub fn png_frames<P: Into<PathBuf>>(dir: P) -> Recorder {
+        Recorder::with_output(Output::PngFrames { dir: dir.into() })
+    }
+
+    /// Keeps only every `n`-th step (the first step is always kept).
+    pub fn every(mut self, n: usize) -> Recorder {
+        self.every = n.max(1);
+        self
+    }
+
+    /// Stops recording after `n` frames, however long the simulation runs.
+    pub fn max_frames(mut self, n: usize) -> Recorder {
+        self.max_frames = n;
+        self
+    }
+
+    /// Pixels per grid cell.
+    pub fn scale(mut self, scale: u32) -> Recorder {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Time each GIF frame is shown, in hundredths of a second.
+    pub fn delay(mut self, centiseconds: u16) -> Recorder {
+        self.delay = centiseconds;
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.output, Output::Disabled)
+    }
+
+    /// Number of frames written so far.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Called by a simulation once per step. `draw` renders the current state
+    /// and is only called if this step is recorded.
+    pub fn record<F>(&mut self, draw: F) -> io::Result<()>
+    where
+        F: FnOnce() -> Image,
+    {
+        let step = self.steps;
+        self.steps += 1;
+        if !self.is_enabled() || !step.is_multiple_of(self.every) || self.frames >= self.max_frames {
+            return Ok(());
+        }
+
+        let image = draw();
+        match &mut self.output {
+            Output::Disabled => {}
+            Output::Gif { path, encoder } => {
+                let width = u16::try_from(image.cols() * self.scale as usize).map_err(io::Error::other)?;
+                let height = u16::try_from(image.rows() * self.scale as usize).map_err(io::Error::other)?;
+                if encoder.is_none() {
+                    let file = BufWriter::new(File::create(&*path)?);
+                    let mut new_encoder = gif::Encoder::new(file, width, height, &[]).map_err(io::Error::other)?;
+                    new_encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+                    *encoder = Some(new_encoder);
+                }
+                let mut frame = gif::Frame::from_rgb_speed(width, height, &image.pixels(self.scale), 10);
+                frame.delay = self.delay;
+                encoder.as_mut().unwrap().write_frame(&frame).map_err(io::Error::other)?;
+            }
+            Output::PngFrames { dir } => {
+                if self.frames == 0 {
+                    fs::create_dir_all(&*dir)?;
+                }
+                image.write_png(dir.join(format!("frame_{:05}.png", self.frames)), self.scale)?;
+            }
+        }
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Finalises the recording and returns the number of frames written.
+    pub fn finish(self) -> io::Result<usize> {
+        if let Output::Gif { encoder: Some(encoder), .. } = self.output {
+            encoder.into_inner().map_err(io::Error::other)?.flush()?;
+        }
+        Ok(self.frames)
+    }
+}
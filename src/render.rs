@@ -0,0 +1,287 @@
+// Terminal rendering of puzzle grids: a base grid of glyphs plus overlays
+// (paths, highlighted cells, labels, region colouring), printed with optional
+// ANSI colours and cropped to a viewport that fits the terminal.
+
+use std::collections::HashMap;
+use std::io::IsTerminal;
+
+/// The 16 standard ANSI terminal colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl Color {
+    /// Colours that are easy to tell apart, used to colour regions by label.
+    pub const PALETTE: [Color; 12] = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::BrightRed,
+        Color::BrightGreen,
+        Color::BrightYellow,
+        Color::BrightBlue,
+        Color::BrightMagenta,
+        Color::BrightCyan,
+    ];
+
+    pub fn from_label(label: usize) -> Color {
+        Color::PALETTE[label % Color::PALETTE.len()]
+    }
+
+    fn ansi_index(&self) -> u8 {
+        *self as u8
+    }
+
+    fn fg_code(&self) -> u8 {
+        match self.ansi_index() {
+            i @ 0..=7 => 30 + i,
+            i => 90 + i - 8,
+        }
+    }
+
+    fn bg_code(&self) -> u8 {
+        self.fg_code() + 10
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub glyph: char,
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+}
+
+/// Direction a path crosses a cell, deciding between `|`, `-` and `+`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+/// A grid of styled cells, built from a puzzle grid and then decorated with overlays.
+/// Cells are addressed as `(row, col)`; overlays outside the grid are ignored.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    pub fn from_grid<T, F>(grid: &[Vec<T>], glyph: F) -> Frame
+    where
+        F: Fn(&T) -> char,
+    {
+        let cells = grid
+            .iter()
+            .map(|row| row.iter().map(|value| Cell { glyph: glyph(value), fg: None, bg: None }).collect())
+            .collect();
+        Frame { cells }
+    }
+
+    /// One row per line of `text`, padded with spaces to the longest line.
+    pub fn from_text(text: &str) -> Frame {
+        let cols = text.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let mut frame = Frame::filled(text.lines().count(), cols, ' ');
+        frame.label(
+            text.lines().enumerate().flat_map(|(row, line)| line.chars().enumerate().map(move |(col, glyph)| ((row, col), glyph))),
+            None,
+        );
+        frame
+    }
+
+    /// A `rows x cols` frame filled with `glyph`.
+    pub fn filled(rows: usize, cols: usize, glyph: char) -> Frame {
+        Frame { cells: vec![vec![Cell { glyph, fg: None, bg: None }; cols]; rows] }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cells.first().map_or(0, |row| row.len())
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Option<&Cell> {
+        self.cells.get(row)?.get(col)
+    }
+
+    fn cell_mut(&mut self, row: usize, col: usize) -> Option<&mut Cell> {
+        self.cells.get_mut(row)?.get_mut(col)
+    }
+
+    /// Draws a path over the grid: `|` or `-` where it crosses a cell in one axis only,
+    /// `+` where it crosses in both.
+    pub fn overlay_path<I>(&mut self, steps: I, color: Option<Color>) -> &mut Frame
+    where
+        I: IntoIterator<Item = ((usize, usize), Axis)>,
+    {
+        let mut axes: HashMap<(usize, usize), (bool, bool)> = HashMap::new();
+        for (position, axis) in steps {
+            let (vertical, horizontal) = axes.entry(position).or_default();
+            match axis {
+                Axis::Vertical => *vertical = true,
+                Axis::Horizontal => *horizontal = true,
+            }
+        }
+
+        for ((row, col), crossed) in axes {
+            if let Some(cell) = self.cell_mut(row, col) {
+                cell.glyph = match crossed {
+                    (true, true) => '+',
+                    (true, false) => '|',
+                    _ => '-',
+                };
+                cell.fg = color.or(cell.fg);
+            }
+        }
+        self
+    }
+
+    /// Gives the cells a background colour, keeping their glyphs.
+    pub fn highlight<I>(&mut self, cells: I, color: Color) -> &mut Frame
+    where
+        I: IntoIterator<Item = (usize, usize)>,
+    {
+        for (row, col) in cells {
+            if let Some(cell) = self.cell_mut(row, col) {
+                cell.bg = Some(color);
+            }
+        }
+        self
+    }
+
+    /// Replaces the glyphs of the cells, e.g. to mark antennas or the guard.
+    pub fn label<I>(&mut self, labels: I, color: Option<Color>) -> &mut Frame
+    where
+        I: IntoIterator<Item = ((usize, usize), char)>,
+    {
+        for ((row, col), glyph) in labels {
+            if let Some(cell) = self.cell_mut(row, col) {
+                cell.glyph = glyph;
+                cell.fg = color.or(cell.fg);
+            }
+        }
+        self
+    }
+
+    /// Colours every cell by its region label, so neighbouring regions stand apart.
+    pub fn color_regions(&mut self, labels: &[Vec<usize>]) -> &mut Frame {
+        for (row, label_row) in labels.iter().enumerate() {
+            for (col, &label) in label_row.iter().enumerate() {
+                if let Some(cell) = self.cell_mut(row, col) {
+                    cell.fg = Some(Color::from_label(label));
+                }
+            }
+        }
+        self
+    }
+
+    /// Renders the part of the frame selected by `options`, one line per row.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let (height, width) = options.viewport_size();
+        let top = options.top.min(self.rows().saturating_sub(height));
+        let left = options.left.min(self.cols().saturating_sub(width));
+        let bottom = top.saturating_add(height).min(self.rows());
+        let right = left.saturating_add(width).min(self.cols());
+
+        let mut out = String::new();
+        for row in &self.cells[top..bottom] {
+            let mut style: (Option<Color>, Option<Color>) = (None, None);
+            for cell in &row[left..right] {
+                if options.color && (cell.fg, cell.bg) != style {
+                    out.push_str("\x1b[0m");
+                    if let Some(fg) = cell.fg {
+                        out.push_str(&format!("\x1b[{}m", fg.fg_code()));
+                    }
+                    if let Some(bg) = cell.bg {
+                        out.push_str(&format!("\x1b[{}m", bg.bg_code()));
+                    }
+                    style = (cell.fg, cell.bg);
+                }
+                out.push(cell.glyph);
+            }
+            if options.color && style != (None, None) {
+                out.push_str("\x1b[0m");
+            }
+            out.push('\n');
+        }
+
+        if bottom - top < self.rows() || right - left < self.cols() {
+            out.push_str(&format!(
+                "[rows {}..{} of {}, cols {}..{} of {}]\n",
+                top, bottom, self.rows(), left, right, self.cols()
+            ));
+        }
+        out
+    }
+
+    pub fn print(&self, options: &RenderOptions) {
+        println!("{}", self.render(options));
+    }
+}
+
+/// How much of a frame to show, and whether to use colours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderOptions {
+    pub color: bool,
+    /// First row and column shown; clamped so the viewport stays inside the frame.
+    pub top: usize,
+    pub left: usize,
+    /// Viewport size; `None` fits the terminal.
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+}
+
+impl Default for RenderOptions {
+    /// Colours when stdout is a terminal and `NO_COLOR` is not set, viewport fitted to the terminal.
+    fn default() -> Self {
+        RenderOptions {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+            top: 0,
+            left: 0,
+            width: None,
+            height: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Moves the viewport so that `(row, col)` is in its middle, e.g. to follow the guard.
+    pub fn centered_on(mut self, row: usize, col: usize) -> RenderOptions {
+        let (height, width) = self.viewport_size();
+        self.top = row.saturating_sub(height / 2);
+        self.left = col.saturating_sub(width / 2);
+        self
+    }
+
+    /// `(rows, cols)` of the viewport. When not printing to a terminal the frame is shown uncropped.
+    fn viewport_size(&self) -> (usize, usize) {
+        let terminal = match std::io::stdout().is_terminal() {
+            true => crossterm::terminal::size().ok(),
+            false => None,
+        };
+        // Keep a line free for the cropping note and the prompt
+        let height = self.height.or(terminal.map(|(_, rows)| (rows as usize).saturating_sub(2).max(1)));
+        let width = self.width.or(terminal.map(|(cols, _)| cols as usize));
+        (height.unwrap_or(usize::MAX), width.unwrap_or(usize::MAX))
+    }
+}
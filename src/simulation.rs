@@ -0,0 +1,188 @@
+// Step-by-step simulations with a reversible history.
+//
+// A `Simulation` only knows how to move forward. The `Debugger` wrapped around it keeps
+// checkpoints of past states, so it can step back by restoring the closest earlier
+// checkpoint and replaying the steps after it, and it can run until a condition or a
+// breakpoint is hit.
+
+/// A simulation that advances one step at a time, such as the guard walking a maze.
+/// `step` must be deterministic: replaying the steps after a restored state has to
+/// lead to the same states again.
+pub trait Simulation {
+    type State: Clone;
+
+    fn state(&self) -> &Self::State;
+
+    /// Replaces the current state, e.g. with one from the history.
+    fn set_state(&mut self, state: Self::State);
+
+    /// Advances by one step. Not called once `is_done` returns true.
+    fn step(&mut self);
+
+    fn is_done(&self) -> bool;
+
+    /// The `(row, col)` the simulation is working on, e.g. the guard, checked by cell breakpoints.
+    fn position(&self) -> Option<(usize, usize)> {
+        None
+    }
+}
+
+/// A condition on the state of a simulation.
+pub type Condition<T> = Box<dyn Fn(&T) -> bool>;
+
+/// A condition checked after every step of `Debugger::run_until`.
+pub enum Breakpoint<S: Simulation> {
+    /// The simulation's position is the cell `(row, col)`.
+    Cell(usize, usize),
+    /// The state satisfies the condition.
+    State(Condition<S::State>),
+}
+
+impl<S: Simulation> Breakpoint<S> {
+    pub fn state<F>(condition: F) -> Breakpoint<S>
+    where
+        F: Fn(&S::State) -> bool + 'static,
+    {
+        Breakpoint::State(Box::new(condition))
+    }
+
+    fn is_hit(&self, simulation: &S) -> bool {
+        match self {
+            Breakpoint::Cell(row, col) => simulation.position() == Some((*row, *col)),
+            Breakpoint::State(condition) => condition(simulation.state()),
+        }
+    }
+}
+
+/// Why `Debugger::run_until` stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// The simulation finished.
+    Done,
+    /// The predicate passed to `run_until` held.
+    Condition,
+    /// The breakpoint with this index was hit.
+    Breakpoint(usize),
+}
+
+pub struct Debugger<S: Simulation> {
+    simulation: S,
+    steps: usize,
+    /// States at steps `0, every, 2 * every, ...`, as far as the simulation has run.
+    checkpoints: Vec<S::State>,
+    every: usize,
+    breakpoints: Vec<Breakpoint<S>>,
+}
+
+impl<S: Simulation> Debugger<S> {
+    /// Starts debugging at the current state of `simulation`, which counts as step 0.
+    pub fn new(simulation: S) -> Debugger<S> {
+        let checkpoints = vec![simulation.state().clone()];
+        Debugger { simulation, steps: 0, checkpoints, every: 64, breakpoints: Vec::new() }
+    }
+
+    /// Keeps a checkpoint every `n` steps: stepping back replays at most `n - 1` steps,
+    /// and the history holds one state per `n` steps.
+    pub fn checkpoint_every(mut self, n: usize) -> Debugger<S> {
+        let steps = self.steps;
+        self.every = n.max(1);
+        self.checkpoints.truncate(1);
+        self.simulation.set_state(self.checkpoints[0].clone());
+        self.steps = 0;
+        self.go_to(steps);
+        self
+    }
+
+    pub fn simulation(&self) -> &S {
+        &self.simulation
+    }
+
+    pub fn state(&self) -> &S::State {
+        self.simulation.state()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.simulation.is_done()
+    }
+
+    /// Number of steps from the first state to the current one.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Adds a breakpoint and returns its index, as reported in `Stop::Breakpoint`.
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint<S>) -> usize {
+        self.breakpoints.push(breakpoint);
+        self.breakpoints.len() - 1
+    }
+
+    /// Removes the breakpoint with this index; the ones after it move down by one.
+    pub fn remove_breakpoint(&mut self, index: usize) -> Breakpoint<S> {
+        self.breakpoints.remove(index)
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint<S>] {
+        &self.breakpoints
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    /// Advances by one step; false if the simulation is already done.
+    pub fn step(&mut self) -> bool {
+        if self.simulation.is_done() {
+            return false;
+        }
+        self.simulation.step();
+        self.steps += 1;
+        if self.steps.is_multiple_of(self.every) && self.checkpoints.len() == self.steps / self.every {
+            self.checkpoints.push(self.simulation.state().clone());
+        }
+        true
+    }
+
+    /// Goes back to the previous state; false at the first state.
+    pub fn step_back(&mut self) -> bool {
+        if self.steps == 0 {
+            return false;
+        }
+        self.go_to(self.steps - 1);
+        true
+    }
+
+    /// Moves to the state after `step` steps, or to the last state if the simulation finishes before.
+    /// Returns the step reached.
+    pub fn go_to(&mut self, step: usize) -> usize {
+        // Start from the closest checkpoint unless the current state is closer
+        let checkpoint = (step / self.every).min(self.checkpoints.len() - 1);
+        if step < self.steps || checkpoint * self.every > self.steps {
+            self.simulation.set_state(self.checkpoints[checkpoint].clone());
+            self.steps = checkpoint * self.every;
+        }
+        while self.steps < step && self.step() {}
+        self.steps
+    }
+
+    /// Steps until `predicate` holds for the state, a breakpoint is hit or the simulation is done.
+    /// Always takes at least one step, so it can be called repeatedly to find the next match.
+    pub fn run_until<F>(&mut self, mut predicate: F) -> Stop
+    where
+        F: FnMut(&S::State) -> bool,
+    {
+        while self.step() {
+            if predicate(self.simulation.state()) {
+                return Stop::Condition;
+            }
+            if let Some(index) = self.breakpoints.iter().position(|breakpoint| breakpoint.is_hit(&self.simulation)) {
+                return Stop::Breakpoint(index);
+            }
+        }
+        Stop::Done
+    }
+
+    /// Steps until a breakpoint is hit or the simulation is done.
+    pub fn run(&mut self) -> Stop {
+        self.run_until(|_| false)
+    }
+}
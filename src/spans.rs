@@ -0,0 +1,137 @@
+// Spans of a one-dimensional space (e.g. blocks on a disk) and an index of free spans
+// that answers "leftmost free span of length >= k starting before offset x" in logarithmic time.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, len: usize) -> Span {
+        Span { start, len }
+    }
+
+    /// First offset after the span.
+    pub fn end(&self) -> usize {
+        self.start + self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A span occupied by the file with the given ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileSpan {
+    pub id: usize,
+    pub span: Span,
+}
+
+/// Free spans bucketed by length, each bucket being a min-heap of start offsets.
+///
+/// A segment tree over the lengths holds the leftmost start of every range of buckets,
+/// so finding the leftmost span of at least a given length costs `O(log L)` for `L`
+/// distinct lengths, and inserting or taking a span `O(log L + log n)`.
+#[derive(Debug, Clone, Default)]
+pub struct FreeSpans {
+    buckets: Vec<BinaryHeap<Reverse<usize>>>,
+    /// `(start, len)` of the leftmost span in each node's range of lengths, `NO_SPAN` if none.
+    /// Node 1 is the root, node `i` has children `2i` and `2i + 1`, and the leaf of length
+    /// `len` is node `width + len`.
+    tree: Vec<(usize, usize)>,
+    /// Number of leaves, a power of two larger than the longest length.
+    width: usize,
+}
+
+const NO_SPAN: (usize, usize) = (usize::MAX, 0);
+
+impl FreeSpans {
+    pub fn new() -> FreeSpans {
+        FreeSpans::default()
+    }
+
+    /// Marks `span` as free. Empty spans are ignored.
+    /// Spans are not merged with adjacent free spans.
+    pub fn insert(&mut self, span: Span) {
+        if span.is_empty() {
+            return;
+        }
+        if self.buckets.len() <= span.len {
+            self.grow(span.len);
+        }
+        self.buckets[span.len].push(Reverse(span.start));
+        self.update(span.len);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.get(1).is_none_or(|&leftmost| leftmost == NO_SPAN)
+    }
+
+    /// Leftmost free span of at least `min_len` blocks that starts before offset `before`.
+    pub fn leftmost_fitting(&self, min_len: usize, before: usize) -> Option<Span> {
+        // Bottom-up query over the leaves `min_len..width`
+        let (mut low, mut high) = (self.width + min_len.max(1), 2 * self.width);
+        let mut leftmost = NO_SPAN;
+        while low < high {
+            if low & 1 == 1 {
+                leftmost = leftmost.min(self.tree[low]);
+                low += 1;
+            }
+            if high & 1 == 1 {
+                high -= 1;
+                leftmost = leftmost.min(self.tree[high]);
+            }
+            low /= 2;
+            high /= 2;
+        }
+        let (start, len) = leftmost;
+        (leftmost != NO_SPAN && start < before).then(|| Span::new(start, len))
+    }
+
+    /// Removes and returns the span [`FreeSpans::leftmost_fitting`] would return.
+    pub fn take_leftmost_fitting(&mut self, min_len: usize, before: usize) -> Option<Span> {
+        let span = self.leftmost_fitting(min_len, before)?;
+        self.buckets[span.len].pop();
+        self.update(span.len);
+        Some(span)
+    }
+
+    /// Allocates `len` blocks at the start of the leftmost fitting free span before `before`,
+    /// returning the span taken. The rest of the free span stays free.
+    pub fn allocate(&mut self, len: usize, before: usize) -> Option<Span> {
+        let free = self.take_leftmost_fitting(len, before)?;
+        self.insert(Span::new(free.start + len, free.len - len));
+        Some(Span::new(free.start, len))
+    }
+
+    /// Adds buckets up to length `len`, rebuilding the tree if it has too few leaves.
+    fn grow(&mut self, len: usize) {
+        self.buckets.resize_with(len + 1, BinaryHeap::new);
+        if self.width > len {
+            return;
+        }
+        self.width = (len + 1).next_power_of_two();
+        self.tree = vec![NO_SPAN; 2 * self.width];
+        for (len, bucket) in self.buckets.iter().enumerate() {
+            self.tree[self.width + len] = bucket.peek().map_or(NO_SPAN, |&Reverse(start)| (start, len));
+        }
+        for node in (1..self.width).rev() {
+            self.tree[node] = self.tree[2 * node].min(self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Refreshes the leaf of length `len` and the nodes above it.
+    fn update(&mut self, len: usize) {
+        let mut node = self.width + len;
+        self.tree[node] = self.buckets[len].peek().map_or(NO_SPAN, |&Reverse(start)| (start, len));
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = self.tree[2 * node].min(self.tree[2 * node + 1]);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+// Minimal SVG document builder for vector exports of puzzle states.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct Svg {
+    width: f64,
+    height: f64,
+    body: String,
+}
+
+/// Escapes text for use in SVG content and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Svg {
+    pub fn new(width: f64, height: f64) -> Svg {
+        Svg { width, height, body: String::new() }
+    }
+
+    /// Opens a `<g>` with the given presentation attributes (e.g. `fill="#ff0000"`)
+    /// and an optional tooltip shown when hovering anything in the group.
+    pub fn begin_group(&mut self, attributes: &str, tooltip: Option<&str>) -> &mut Svg {
+        let _ = writeln!(self.body, "<g {}>", attributes);
+        if let Some(tooltip) = tooltip {
+            let _ = writeln!(self.body, "<title>{}</title>", escape(tooltip));
+        }
+        self
+    }
+
+    pub fn end_group(&mut self) -> &mut Svg {
+        self.body.push_str("</g>\n");
+        self
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, attributes: &str) -> &mut Svg {
+        let _ = writeln!(self.body, r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#, x, y, width, height, attributes);
+        self
+    }
+
+    pub fn polyline(&mut self, points: &[(f64, f64)], attributes: &str) -> &mut Svg {
+        let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+        let _ = writeln!(self.body, r#"<polyline points="{}" fill="none" {}/>"#, points.join(" "), attributes);
+        self
+    }
+
+    pub fn text(&mut self, x: f64, y: f64, content: &str, attributes: &str) -> &mut Svg {
+        let _ = writeln!(self.body, r#"<text x="{}" y="{}" {}>{}</text>"#, x, y, attributes, escape(content));
+        self
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{body}</svg>\n",
+            w = self.width,
+            h = self.height,
+            body = self.body
+        )
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
@@ -0,0 +1,296 @@
+// Interactive explorer: lists the days, runs their parts with timings, and steps
+// forward and back through the states of the days that provide a viewer.
+//
+// Days list: Up/Down select, 1/2 run a part, Enter or v view, q quit.
+// View: Left/Right step, PgUp/PgDn 100 steps, Home/End first/last step,
+//       w/a/s/d scroll, f follow the focus again, b breakpoint on the focus (or the
+//       centre of the view), t breakpoint on the day's condition, c continue to the
+//       next breakpoint, Esc back.
+
+use std::collections::HashMap;
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{self, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::DefaultTerminal;
+
+use crate::days::{self, Day, Outcome, DAYS};
+use crate::render::{Color, Frame};
+use crate::viewer::Viewer;
+
+/// Runs the explorer until the user quits.
+pub fn run() -> io::Result<()> {
+    ratatui::run(|terminal| Explorer::new().run(terminal))
+}
+
+/// The states of one day, with the part of the frame that is in view.
+struct View {
+    day: &'static Day,
+    viewer: Box<dyn Viewer>,
+    top: usize,
+    left: usize,
+    /// Keep the snapshot focus centred; scrolling by hand turns this off.
+    follow: bool,
+    /// Size of the frame area at the last draw, for centring and scrolling.
+    area: (usize, usize),
+    /// Shown instead of the snapshot status until the next key, e.g. where `c` stopped.
+    message: Option<String>,
+}
+
+impl View {
+    fn open(day: &'static Day) -> Result<View, String> {
+        let open_view = day.view.ok_or_else(|| format!("day {} has nothing to view", day.number))?;
+        let input = day.input().map_err(|err| format!("{}: {}", day.input_path().display(), err))?;
+        let viewer = open_view(&input).ok_or_else(|| format!("day {} has no states", day.number))?;
+        Ok(View { day, viewer, top: 0, left: 0, follow: true, area: (0, 0), message: None })
+    }
+
+    /// The cell a breakpoint goes on: the focus while following it, otherwise the centre of the view.
+    fn breakpoint_cell(&self) -> (usize, usize) {
+        match (self.follow, self.viewer.snapshot().focus) {
+            (true, Some(focus)) => focus,
+            _ => (self.top + self.area.0 / 2, self.left + self.area.1 / 2),
+        }
+    }
+
+    fn toggle_cell_breakpoint(&mut self) {
+        let (row, col) = self.breakpoint_cell();
+        self.message = Some(match self.viewer.toggle_cell_breakpoint(row, col) {
+            Some(true) => format!("breakpoint at row {}, column {}", row, col),
+            Some(false) => format!("removed the breakpoint at row {}, column {}", row, col),
+            None => format!("day {} has no breakpoints", self.day.number),
+        });
+    }
+
+    fn toggle_condition(&mut self) {
+        self.message = Some(match self.viewer.toggle_condition() {
+            Some((name, true)) => format!("breakpoint when {}", name),
+            Some((name, false)) => format!("removed the breakpoint when {}", name),
+            None => format!("day {} has no condition to break on", self.day.number),
+        });
+    }
+
+    fn run(&mut self) {
+        let stop = self.viewer.run();
+        self.message = Some(stop.unwrap_or_else(|| format!("day {} has no breakpoints", self.day.number)));
+    }
+
+    fn scroll(&mut self, rows: isize, cols: isize) {
+        self.follow = false;
+        self.top = self.top.saturating_add_signed(rows);
+        self.left = self.left.saturating_add_signed(cols);
+    }
+
+    /// Keeps the viewport inside the frame, centred on the focus when following it.
+    fn fit_viewport(&mut self, height: usize, width: usize) {
+        self.area = (height, width);
+        let snapshot = self.viewer.snapshot();
+        if let (true, Some((row, col))) = (self.follow, snapshot.focus) {
+            self.top = row.saturating_sub(height / 2);
+            self.left = col.saturating_sub(width / 2);
+        }
+        self.top = self.top.min(snapshot.frame.rows().saturating_sub(height));
+        self.left = self.left.min(snapshot.frame.cols().saturating_sub(width));
+    }
+}
+
+struct Explorer {
+    days: ListState,
+    outcomes: HashMap<(u32, u32), Outcome>,
+    view: Option<View>,
+    message: Option<String>,
+    quit: bool,
+}
+
+impl Explorer {
+    fn new() -> Explorer {
+        Explorer {
+            days: ListState::default().with_selected(Some(0)),
+            outcomes: HashMap::new(),
+            view: None,
+            message: None,
+            quit: false,
+        }
+    }
+
+    fn selected_day(&self) -> &'static Day {
+        &DAYS[self.days.selected().unwrap_or(0)]
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key.code, terminal)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyCode, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        if let Some(view) = &mut self.view {
+            let (height, width) = view.area;
+            let steps = view.viewer.steps();
+            view.message = None;
+            match key {
+                KeyCode::Esc | KeyCode::Char('q') => self.view = None,
+                KeyCode::Right => _ = view.viewer.step(),
+                KeyCode::Left => _ = view.viewer.step_back(),
+                KeyCode::PageDown => _ = view.viewer.go_to(steps + 100),
+                KeyCode::PageUp => _ = view.viewer.go_to(steps.saturating_sub(100)),
+                KeyCode::Home => _ = view.viewer.go_to(0),
+                KeyCode::End => _ = view.viewer.go_to(usize::MAX),
+                KeyCode::Char('w') => view.scroll(-(height as isize / 2), 0),
+                KeyCode::Char('s') => view.scroll(height as isize / 2, 0),
+                KeyCode::Char('a') => view.scroll(0, -(width as isize / 2)),
+                KeyCode::Char('d') => view.scroll(0, width as isize / 2),
+                KeyCode::Char('f') => view.follow = true,
+                KeyCode::Char('b') => view.toggle_cell_breakpoint(),
+                KeyCode::Char('t') => view.toggle_condition(),
+                KeyCode::Char('c') => view.run(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down => self.days.select_next(),
+            KeyCode::Up => self.days.select_previous(),
+            KeyCode::Char(c @ ('1' | '2')) => {
+                let day = self.selected_day();
+                let part = c.to_digit(10).unwrap();
+                self.message = Some(format!("Running day {} part {}...", day.number, part));
+                terminal.draw(|frame| self.draw(frame))?;
+                self.outcomes.insert((day.number, part), days::run_part(day, part));
+                self.message = None;
+            }
+            KeyCode::Enter | KeyCode::Char('v') => match View::open(self.selected_day()) {
+                Ok(view) => {
+                    self.view = Some(view);
+                    self.message = None;
+                }
+                Err(message) => self.message = Some(message),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut ratatui::Frame) {
+        match &mut self.view {
+            Some(view) => draw_view(frame, view),
+            None => self.draw_days(frame),
+        }
+    }
+
+    fn draw_days(&mut self, frame: &mut ratatui::Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, details_area] =
+            Layout::horizontal([Constraint::Length(36), Constraint::Min(1)]).areas(main);
+
+        let items: Vec<ListItem> = DAYS
+            .iter()
+            .map(|day| {
+                let marker = if day.view.is_some() { " *" } else { "" };
+                ListItem::new(format!("Day {:>2}: {}{}", day.number, day.title, marker))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(" Advent of Code 2024 "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.days);
+
+        let day = self.selected_day();
+        let mut lines = vec![];
+        for part in 1..=2 {
+            let text = match (day.part(part), self.outcomes.get(&(day.number, part))) {
+                (None, _) => Span::styled("not implemented", Style::new().fg(style::Color::DarkGray)),
+                (Some(_), None) => Span::styled(format!("press {} to run", part), Style::new().fg(style::Color::DarkGray)),
+                (Some(_), Some(Outcome::Answer(answer, elapsed))) => {
+                    Span::raw(format!("{}  ({:.2?})", answer, elapsed))
+                }
+                (Some(_), Some(Outcome::Failed(message))) => {
+                    Span::styled(message.clone(), Style::new().fg(style::Color::Red))
+                }
+            };
+            lines.push(Line::from(vec![Span::raw(format!("Part {}: ", part)), text]));
+        }
+        if day.view.is_some() {
+            lines.push(Line::raw(""));
+            lines.push(Line::raw("Press Enter to step through the states."));
+        }
+        let title = format!(" Day {}: {} ", day.number, day.title);
+        frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(title)), details_area);
+
+        let help = "Up/Down select  1/2 run part  Enter view (*)  q quit";
+        frame.render_widget(Paragraph::new(self.message.as_deref().unwrap_or(help)), footer);
+    }
+}
+
+fn draw_view(frame: &mut ratatui::Frame, view: &mut View) {
+    let [main, status, footer] =
+        Layout::vertical([Constraint::Min(1), Constraint::Length(1), Constraint::Length(1)]).areas(frame.area());
+
+    let title = format!(" Day {}: {} - step {} ", view.day.number, view.day.title, view.viewer.steps());
+    let block = Block::bordered().title(title);
+    let inner = block.inner(main);
+    frame.render_widget(block, main);
+    view.fit_viewport(inner.height as usize, inner.width as usize);
+    let snapshot = view.viewer.snapshot();
+    frame.render_widget(Paragraph::new(frame_lines(&snapshot.frame, view.top, view.left, inner)), inner);
+
+    frame.render_widget(Paragraph::new(view.message.as_deref().unwrap_or(&snapshot.status)), status);
+    let help = "Left/Right step  PgUp/PgDn 100  Home/End  w/a/s/d scroll  f follow  b/t breakpoint  c continue  Esc back";
+    frame.render_widget(Paragraph::new(help).style(Style::new().fg(style::Color::DarkGray)), footer);
+}
+
+/// The part of `frame` starting at `(top, left)` that fits into `area`, as styled lines.
+fn frame_lines(frame: &Frame, top: usize, left: usize, area: Rect) -> Vec<Line<'static>> {
+    let bottom = top.saturating_add(area.height as usize).min(frame.rows());
+    let right = left.saturating_add(area.width as usize).min(frame.cols());
+    (top..bottom)
+        .map(|row| {
+            let spans: Vec<Span> = (left..right)
+                .filter_map(|col| frame.cell(row, col))
+                .map(|cell| {
+                    let mut style = Style::new();
+                    if let Some(fg) = cell.fg {
+                        style = style.fg(tui_color(fg));
+                    }
+                    if let Some(bg) = cell.bg {
+                        style = style.bg(tui_color(bg));
+                    }
+                    Span::styled(cell.glyph.to_string(), style)
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn tui_color(color: Color) -> style::Color {
+    match color {
+        Color::Black => style::Color::Black,
+        Color::Red => style::Color::Red,
+        Color::Green => style::Color::Green,
+        Color::Yellow => style::Color::Yellow,
+        Color::Blue => style::Color::Blue,
+        Color::Magenta => style::Color::Magenta,
+        Color::Cyan => style::Color::Cyan,
+        Color::White => style::Color::Gray,
+        Color::BrightBlack => style::Color::DarkGray,
+        Color::BrightRed => style::Color::LightRed,
+        Color::BrightGreen => style::Color::LightGreen,
+        Color::BrightYellow => style::Color::LightYellow,
+        Color::BrightBlue => style::Color::LightBlue,
+        Color::BrightMagenta => style::Color::LightMagenta,
+        Color::BrightCyan => style::Color::LightCyan,
+        Color::BrightWhite => style::Color::White,
+    }
+}
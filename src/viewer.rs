@@ -0,0 +1,236 @@
+// Stepping through the states of a day, as the explorer does. Days that can only compute
+// the state after a given number of steps are asked again for every move, while
+// simulations keep one live `Debugger` and move a step at a time from where they are,
+// and can run on to a breakpoint.
+
+use std::rc::Rc;
+
+use crate::days::{Snapshot, SnapshotFn};
+use crate::render::Color;
+use crate::simulation::{Breakpoint, Debugger, Simulation, Stop};
+
+/// The states of one day, with a current one to move from.
+pub trait Viewer {
+    /// Number of steps from the first state to the current one.
+    fn steps(&self) -> usize;
+
+    fn snapshot(&self) -> &Snapshot;
+
+    /// Moves to the next state; false at the last one.
+    fn step(&mut self) -> bool {
+        let steps = self.steps();
+        self.go_to(steps + 1) > steps
+    }
+
+    /// Moves to the previous state; false at the first one.
+    fn step_back(&mut self) -> bool {
+        let steps = self.steps();
+        steps > 0 && self.go_to(steps - 1) < steps
+    }
+
+    /// Moves to the state after `step` steps, or to the last state if there are fewer.
+    /// Returns the step reached.
+    fn go_to(&mut self, step: usize) -> usize;
+
+    /// Adds a breakpoint on the cell `(row, col)`, or removes the one there. Returns whether
+    /// the cell has a breakpoint now, or `None` if the viewer has no breakpoints.
+    fn toggle_cell_breakpoint(&mut self, _row: usize, _col: usize) -> Option<bool> {
+        None
+    }
+
+    /// Turns the breakpoint on the viewer's own condition on or off. Returns the name of the
+    /// condition and whether it is on now, or `None` if the viewer has no condition.
+    fn toggle_condition(&mut self) -> Option<(&'static str, bool)> {
+        None
+    }
+
+    /// Steps until a breakpoint is hit or the last state. Returns where it stopped, or `None`
+    /// if the viewer has no breakpoints.
+    fn run(&mut self) -> Option<String> {
+        None
+    }
+}
+
+/// A [`Viewer`] over a [`SnapshotFn`], which computes every state from the input again.
+/// Meant for days that are cheap to compute at any step.
+pub struct SnapshotViewer {
+    input: String,
+    snapshot_fn: SnapshotFn,
+    step: usize,
+    snapshot: Snapshot,
+}
+
+impl SnapshotViewer {
+    /// `None` if there is not even a first state.
+    pub fn new(input: &str, snapshot_fn: SnapshotFn) -> Option<SnapshotViewer> {
+        let snapshot = snapshot_fn(input, 0)?;
+        Some(SnapshotViewer { input: input.to_string(), snapshot_fn, step: 0, snapshot })
+    }
+
+    /// Boxed for [`crate::days::Day::view`].
+    pub fn open(input: &str, snapshot_fn: SnapshotFn) -> Option<Box<dyn Viewer>> {
+        SnapshotViewer::new(input, snapshot_fn).map(|viewer| Box::new(viewer) as Box<dyn Viewer>)
+    }
+
+    fn snapshot_at(&self, step: usize) -> Option<Snapshot> {
+        (self.snapshot_fn)(&self.input, step)
+    }
+}
+
+impl Viewer for SnapshotViewer {
+    fn steps(&self) -> usize {
+        self.step
+    }
+
+    fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    fn go_to(&mut self, step: usize) -> usize {
+        if let Some(snapshot) = self.snapshot_at(step) {
+            self.step = step;
+            self.snapshot = snapshot;
+            return step;
+        }
+        if step < self.step {
+            return self.step;
+        }
+
+        // The current step exists and `step` does not: binary search for the last step in between
+        let (mut valid, mut invalid) = (self.step, step);
+        while invalid - valid > 1 {
+            let middle = valid + (invalid - valid) / 2;
+            match self.snapshot_at(middle) {
+                Some(_) => valid = middle,
+                None => invalid = middle,
+            }
+        }
+        if valid != self.step {
+            self.snapshot = self.snapshot_at(valid).unwrap();
+            self.step = valid;
+        }
+        self.step
+    }
+}
+
+/// Draws the current state of a simulation after the given number of steps.
+pub type Render<S> = Box<dyn Fn(&S, usize) -> Snapshot>;
+
+/// A condition offered as a breakpoint, shared with the breakpoint while it is on.
+type SharedCondition<T> = Rc<dyn Fn(&T) -> bool>;
+
+/// A [`Viewer`] over a simulation held in a [`Debugger`], so that stepping forward costs one
+/// step and stepping back replays at most the steps since the last checkpoint. Cells with a
+/// breakpoint are highlighted in the frame.
+pub struct DebuggerViewer<S: Simulation> {
+    debugger: Debugger<S>,
+    render: Render<S>,
+    snapshot: Snapshot,
+    /// The named condition offered by `toggle_condition`.
+    condition: Option<(&'static str, SharedCondition<S::State>)>,
+}
+
+impl<S: Simulation> DebuggerViewer<S> {
+    /// Starts at the current state of `debugger`, which should have no breakpoints yet.
+    pub fn new(debugger: Debugger<S>, render: Render<S>) -> DebuggerViewer<S> {
+        let snapshot = render(debugger.simulation(), debugger.steps());
+        DebuggerViewer { debugger, render, snapshot, condition: None }
+    }
+
+    /// Offers a breakpoint on `condition`, e.g. the guard being about to turn.
+    pub fn with_condition<F>(mut self, name: &'static str, condition: F) -> DebuggerViewer<S>
+    where
+        F: Fn(&S::State) -> bool + 'static,
+    {
+        self.condition = Some((name, Rc::new(condition)));
+        self
+    }
+
+    pub fn debugger(&self) -> &Debugger<S> {
+        &self.debugger
+    }
+
+    fn redraw(&mut self) {
+        self.snapshot = (self.render)(self.debugger.simulation(), self.debugger.steps());
+        let cells = self.debugger.breakpoints().iter().filter_map(|breakpoint| match breakpoint {
+            Breakpoint::Cell(row, col) => Some((*row, *col)),
+            Breakpoint::State(_) => None,
+        });
+        self.snapshot.frame.highlight(cells, Color::Red);
+    }
+}
+
+impl<S: Simulation> Viewer for DebuggerViewer<S>
+where
+    S::State: 'static,
+{
+    fn steps(&self) -> usize {
+        self.debugger.steps()
+    }
+
+    fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    fn step(&mut self) -> bool {
+        let moved = self.debugger.step();
+        if moved {
+            self.redraw();
+        }
+        moved
+    }
+
+    fn step_back(&mut self) -> bool {
+        let moved = self.debugger.step_back();
+        if moved {
+            self.redraw();
+        }
+        moved
+    }
+
+    fn go_to(&mut self, step: usize) -> usize {
+        let steps = self.debugger.steps();
+        if self.debugger.go_to(step) != steps {
+            self.redraw();
+        }
+        self.debugger.steps()
+    }
+
+    fn toggle_cell_breakpoint(&mut self, row: usize, col: usize) -> Option<bool> {
+        let existing = self.debugger.breakpoints().iter().position(|breakpoint| {
+            matches!(breakpoint, Breakpoint::Cell(r, c) if (*r, *c) == (row, col))
+        });
+        match existing {
+            Some(index) => _ = self.debugger.remove_breakpoint(index),
+            None => _ = self.debugger.add_breakpoint(Breakpoint::Cell(row, col)),
+        }
+        self.redraw();
+        Some(existing.is_none())
+    }
+
+    fn toggle_condition(&mut self) -> Option<(&'static str, bool)> {
+        let (name, condition) = self.condition.clone()?;
+        // The viewer adds no other state breakpoints
+        let existing = self.debugger.breakpoints().iter().position(|breakpoint| matches!(breakpoint, Breakpoint::State(_)));
+        match existing {
+            Some(index) => _ = self.debugger.remove_breakpoint(index),
+            None => _ = self.debugger.add_breakpoint(Breakpoint::state(move |state| condition(state))),
+        }
+        Some((name, existing.is_none()))
+    }
+
+    fn run(&mut self) -> Option<String> {
+        let stop = self.debugger.run();
+        self.redraw();
+        let steps = self.debugger.steps();
+        let message = match stop {
+            Stop::Done => format!("reached the last state at step {}", steps),
+            Stop::Condition => unreachable!("`run` has no condition of its own"),
+            Stop::Breakpoint(index) => match &self.debugger.breakpoints()[index] {
+                Breakpoint::Cell(row, col) => format!("breakpoint at row {}, column {} hit at step {}", row, col, steps),
+                Breakpoint::State(_) => format!("{} at step {}", self.condition.as_ref().unwrap().0, steps),
+            },
+        };
+        Some(message)
+    }
+}
@@ -0,0 +1,62 @@
+// The Aho–Corasick automaton against the classic examples and a brute-force search.
+
+use aoc2024::aho_corasick::{AhoCorasick, Match};
+use aoc2024::random::Rng;
+
+fn find(patterns: &[&str], text: &str) -> Vec<(usize, usize)> {
+    let mut found: Vec<(usize, usize)> =
+        AhoCorasick::new(patterns).find_all(text.chars()).into_iter().map(|m| (m.start, m.pattern)).collect();
+    found.sort_unstable();
+    found
+}
+
+/// Every `(start, pattern)` by comparing every pattern at every position.
+fn brute_force(patterns: &[&str], text: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    for start in 0..text.len() {
+        for (i, pattern) in patterns.iter().enumerate() {
+            if !pattern.is_empty() && text[start..].starts_with(pattern) {
+                found.push((start, i));
+            }
+        }
+    }
+    found
+}
+
+#[test]
+fn overlapping_patterns_are_all_found() {
+    let patterns = ["he", "she", "his", "hers"];
+    assert_eq!(find(&patterns, "ushers"), [(1, 1), (2, 0), (2, 3)]);
+    assert_eq!(find(&["aa"], "aaaa"), [(0, 0), (1, 0), (2, 0)]);
+}
+
+#[test]
+fn a_suffix_of_another_pattern_is_found_through_failure_links() {
+    let patterns = ["abcd", "bcd", "cd", "d", "bc"];
+    assert_eq!(find(&patterns, "xabcdx"), [(1, 0), (2, 1), (2, 4), (3, 2), (4, 3)]);
+}
+
+#[test]
+fn matches_are_ordered_by_their_end() {
+    let matches = AhoCorasick::new(&["abc", "b"]).find_all("abc".chars());
+    assert_eq!(matches, [Match { start: 1, len: 1, pattern: 1 }, Match { start: 0, len: 3, pattern: 0 }]);
+}
+
+#[test]
+fn an_empty_pattern_never_matches() {
+    let automaton = AhoCorasick::new(&["", "a"]);
+    assert_eq!(automaton.pattern_len(0), 0);
+    assert_eq!(find(&["", "a"], "aba"), [(0, 1), (2, 1)]);
+    assert!(AhoCorasick::new(&[""]).find_all("abc".chars()).is_empty());
+    assert_eq!(AhoCorasick::new::<&str>(&[]).max_len(), 0);
+}
+
+#[test]
+fn agrees_with_brute_force() {
+    let patterns = ["ab", "aba", "b", "bab", "abab", "ba", "bb"];
+    let mut rng = Rng::new(0x9e37_79b9);
+    for len in 0..60 {
+        let text: String = (0..len).map(|_| if rng.chance(0.5) { 'a' } else { 'b' }).collect();
+        assert_eq!(find(&patterns, &text), brute_force(&patterns, &text), "{}", text);
+    }
+}
@@ -0,0 +1,96 @@
+// Day 1 on small hand-made lists: reading them, the answers for IDs near the top of `u64`,
+// and every metric between the columns of a three-column list.
+
+use std::io;
+
+use aoc2024::day01::{self, LocationColumns, LocationLists, Metric};
+
+#[test]
+fn negative_ids_are_invalid_data() {
+    let err = LocationLists::read("3   4\n-3   2\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().starts_with("2:1:"), "{}", err);
+}
+
+#[test]
+fn ids_beyond_u64_are_invalid_data() {
+    let err = LocationLists::read("18446744073709551616   1\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn large_ids_do_not_overflow() {
+    let max = u64::MAX;
+    // Sorted, left `1, max, max` pairs with right `0, 0, max`, which holds `max` once
+    let input = format!("{max}   0\n1   {max}\n{max}   0\n");
+    let expected = (u128::from(max) + 1, 2 * u128::from(max));
+    assert_eq!((day01::part_one(&input), day01::part_two(&input)), expected);
+
+    assert_eq!(day01::solve_stream(&mut input.as_bytes()).unwrap(), expected);
+    let answers = day01::solve_external(&mut input.as_bytes(), 0, &std::env::temp_dir()).unwrap();
+    assert_eq!((answers.distance, answers.similarity), expected);
+}
+
+/// Columns `1 2 3 4`, `4 4 5 6` and `2 2 1 9`: the second and third have ties.
+const COLUMNS: &str = "1 4 2\n2 4 2\n3 5 1\n4 6 9\n";
+
+fn compare(metric: Metric) -> Vec<Vec<f64>> {
+    LocationColumns::read(COLUMNS.as_bytes()).unwrap().compare(metric)
+}
+
+fn assert_close(actual: &[Vec<f64>], expected: &[[f64; 3]; 3]) {
+    for (row, expected_row) in actual.iter().zip(expected) {
+        for (value, expected) in row.iter().zip(expected_row) {
+            assert!((value - expected).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+}
+
+#[test]
+fn columns_must_have_the_same_length() {
+    let err = LocationColumns::read("1 2 3\n4 5\n".as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn distance_pairs_sorted_columns() {
+    assert_close(&compare(Metric::Distance), &[[0.0, 9.0, 6.0], [9.0, 0.0, 11.0], [6.0, 11.0, 0.0]]);
+    let (a, b, c) = (21f64.sqrt(), 26f64.sqrt(), 31f64.sqrt());
+    assert_close(&compare(Metric::EuclideanDistance), &[[0.0, a, b], [a, 0.0, c], [b, c, 0.0]]);
+}
+
+#[test]
+fn similarity_weighs_ids_by_both_counts() {
+    assert_close(&compare(Metric::Similarity), &[[10.0, 8.0, 5.0], [8.0, 27.0, 0.0], [5.0, 0.0, 18.0]]);
+}
+
+#[test]
+fn jaccard_counts_repeated_ids() {
+    assert_close(&compare(Metric::Jaccard), &[[1.0, 1.0 / 7.0, 1.0 / 3.0], [1.0 / 7.0, 1.0, 0.0], [1.0 / 3.0, 0.0, 1.0]]);
+}
+
+#[test]
+fn jaccard_of_empty_multisets_is_one() {
+    let lists = LocationColumns { columns: vec![Vec::new(), Vec::new()] };
+    assert_eq!(lists.compare(Metric::Jaccard), [[1.0, 1.0], [1.0, 1.0]]);
+}
+
+#[test]
+fn rank_correlation_averages_ties() {
+    let (a, b, c) = (0.9f64.sqrt(), 0.1f64.sqrt(), 1.0 / 3.0);
+    assert_close(&compare(Metric::RankCorrelation), &[[1.0, a, b], [a, 1.0, c], [b, c, 1.0]]);
+}
+
+#[test]
+fn rank_correlation_of_a_constant_column_is_nan() {
+    let lists = LocationColumns { columns: vec![vec![1, 2, 3], vec![7, 7, 7]] };
+    assert!(lists.compare(Metric::RankCorrelation)[0][1].is_nan());
+}
+
+#[test]
+fn metrics_round_trip_through_their_names() {
+    for metric in Metric::ALL {
+        assert_eq!(metric.name().parse::<Metric>(), Ok(metric));
+    }
+    assert!("manhattan".parse::<Metric>().is_err());
+}
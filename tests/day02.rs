@@ -0,0 +1,69 @@
+// The Day 2 problem dampener against trying every set of levels to remove, on short random
+// reports under a few different safety rules.
+
+use aoc2024::day02::{Direction, SafetyRules};
+use aoc2024::random::Rng;
+
+/// Fewest levels to remove from `report` to make it safe, trying every subset, if at most `max_removals`.
+fn fewest_removals(rules: &SafetyRules, report: &[i32], max_removals: usize) -> Option<usize> {
+    (0u32..1 << report.len())
+        .filter(|removed| removed.count_ones() as usize <= max_removals)
+        .filter(|removed| {
+            let kept: Vec<i32> = (0..report.len()).filter(|&i| removed & (1 << i) == 0).map(|i| report[i]).collect();
+            rules.check(&kept).is_safe()
+        })
+        .map(|removed| removed.count_ones() as usize)
+        .min()
+}
+
+fn rule_sets() -> Vec<SafetyRules> {
+    let puzzle = SafetyRules::default();
+    vec![
+        puzzle,
+        SafetyRules { direction: Direction::Increasing, ..puzzle },
+        SafetyRules { direction: Direction::Decreasing, ..puzzle },
+        SafetyRules { allow_plateaus: true, ..puzzle },
+        SafetyRules { min_step: 2, max_step: 5, ..puzzle },
+    ]
+}
+
+#[test]
+fn dampen_removes_as_few_levels_as_brute_force() {
+    let mut rng = Rng::new(45);
+    for rules in rule_sets() {
+        for _ in 0..1500 {
+            let len = rng.range(0, 9) as usize;
+            // Levels close together, so that many reports are nearly safe
+            let mut level = rng.range(0, 20) as i32;
+            let report: Vec<i32> = (0..len)
+                .map(|_| {
+                    level += rng.range(-3, 4) as i32;
+                    level
+                })
+                .collect();
+
+            for max_removals in 0..=3 {
+                let expected = fewest_removals(&rules, &report, max_removals);
+                let removed = rules.dampen(&report, max_removals);
+                assert_eq!(removed.as_ref().map(Vec::len), expected, "{:?} {:?} k = {}", rules, report, max_removals);
+
+                let Some(removed) = removed else { continue };
+                assert!(removed.windows(2).all(|w| w[0] < w[1]) && removed.iter().all(|&i| i < len), "{:?}", removed);
+                let kept: Vec<i32> = (0..len).filter(|i| !removed.contains(i)).map(|i| report[i]).collect();
+                assert!(rules.check(&kept).is_safe(), "{:?} without {:?} is unsafe", report, removed);
+            }
+        }
+    }
+}
+
+#[test]
+fn dampen_picks_the_cheaper_direction() {
+    let rules = SafetyRules::default();
+    // Increasing needs two removals (9, 8), decreasing needs four
+    assert_eq!(rules.dampen(&[1, 9, 2, 8, 3, 4], 3), Some(vec![1, 3]));
+    assert_eq!(rules.dampen(&[1, 9, 2, 8, 3, 4], 1), None);
+    // Decreasing needs one removal (5), increasing needs three
+    assert_eq!(rules.dampen(&[9, 8, 5, 7, 6], 3), Some(vec![2]));
+    let increasing = SafetyRules { direction: Direction::Increasing, ..rules };
+    assert_eq!(increasing.dampen(&[9, 8, 5, 7, 6], 3).map(|removed| removed.len()), Some(3));
+}
@@ -0,0 +1,91 @@
+// The Day 3 interpreter on instruction sets of its own, with a state other than the puzzle's,
+// and reading memory as a stream in chunks of every size up to the longest instruction.
+
+use aoc2024::day03::{self, interpret, InstructionSet, Interpreter, Lexer, Operand, State};
+use aoc2024::random::Rng;
+
+/// A single register, for instructions the puzzle does not have.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Register {
+    value: i64,
+}
+
+fn register_set() -> InstructionSet<Register> {
+    InstructionSet::<Register>::empty()
+        .with("set", &[Operand::NUMBER.signed()], |register, args| register.value = args[0])
+        .with("add", &[Operand::NUMBER.signed()], |register, args| register.value += args[0])
+        .with("neg", &[], |register, _| register.value = -register.value)
+}
+
+#[test]
+fn effects_change_a_state_of_their_own() {
+    let set = register_set();
+    assert_eq!(interpret("set(5)xneg()add(-2)?add(10", &set), Register { value: -7 });
+    assert_eq!(interpret("neg()", &set), Register { value: 0 });
+}
+
+#[test]
+fn an_interpreter_can_start_from_any_state() {
+    let set = register_set();
+    let mut interpreter = Interpreter::with_state(&set, Register { value: 3 });
+    assert_eq!(*interpreter.run(Lexer::new("neg()add(1)neg()", &set)), Register { value: 2 });
+    let counts: Vec<(&str, u64)> = interpreter.counts().collect();
+    assert_eq!(counts, [("set", 0), ("add", 1), ("neg", 2)]);
+}
+
+/// The state and counts of running `memory` through a stream of `chunk_size` byte chunks.
+fn run_stream(memory: &str, set: &InstructionSet, chunk_size: usize) -> (State, Vec<(String, u64)>) {
+    let mut interpreter = Interpreter::new(set);
+    let state = *interpreter.run_stream(&mut memory.as_bytes(), chunk_size).unwrap();
+    (state, interpreter.counts().map(|(name, count)| (name.to_string(), count)).collect())
+}
+
+/// The same as [`run_stream`], with the whole memory at once.
+fn run_whole(memory: &str, set: &InstructionSet) -> (State, Vec<(String, u64)>) {
+    let mut interpreter = Interpreter::new(set);
+    let state = *interpreter.run(Lexer::new(memory, set));
+    assert_eq!(state, interpret(memory, set));
+    (state, interpreter.counts().map(|(name, count)| (name.to_string(), count)).collect())
+}
+
+#[test]
+fn instructions_split_between_chunks_are_found() {
+    let set = InstructionSet::default();
+    let memories = [
+        "mul(12,3".to_string() + "4)",
+        "don't()mul(2,3)do()mul(4,5)".to_string(),
+        // An operand cut short by the chunk end must not match without its last digits
+        "mul(123,4567)mul(1,23".to_string() + "4)x",
+        "mmul(mul(1,2)mul(3,4)mu".to_string(),
+    ];
+    for memory in &memories {
+        let expected = run_whole(memory, &set);
+        for chunk_size in 1..=set.max_len() + 1 {
+            assert_eq!(run_stream(memory, &set, chunk_size), expected, "{:?} in chunks of {}", memory, chunk_size);
+        }
+    }
+}
+
+#[test]
+fn streams_of_generated_memory_agree_with_interpret() {
+    let set = InstructionSet::default();
+    for seed in 0..20 {
+        let memory = day03::generate(&mut Rng::new(seed), 30);
+        let expected = run_whole(&memory, &set);
+        for chunk_size in 1..=set.max_len() + 1 {
+            assert_eq!(run_stream(&memory, &set, chunk_size), expected, "seed {} in chunks of {}", seed, chunk_size);
+        }
+    }
+}
+
+#[test]
+fn products_of_wide_operands_do_not_overflow() {
+    let wide = Operand::digits(1, 18);
+    // The puzzle's `mul`, reading operands of up to 18 digits
+    let mul = InstructionSet::default().instructions()[0].effect;
+    let set = InstructionSet::<State>::empty().with("mul", &[wide, wide], mul);
+    let largest = 999_999_999_999_999_999i128;
+    let memory = format!("mul({},{})mul({},{})", largest, largest, largest, largest);
+    assert_eq!(interpret(&memory, &set).total, 2 * largest * largest);
+    assert_eq!(run_stream(&memory, &set, 7).0.total, 2 * largest * largest);
+}
@@ -0,0 +1,27 @@
+// Day 4 word search on tiny grids, where words can meet themselves.
+
+use aoc2024::day04::{word_search, SearchOptions};
+
+fn grid(rows: &[&str]) -> Vec<Vec<char>> {
+    rows.iter().map(|row| row.chars().collect()).collect()
+}
+
+#[test]
+fn a_one_letter_word_is_found_once_per_cell() {
+    assert_eq!(word_search(&grid(&["C"]), &["C"], SearchOptions::default()).len(), 1);
+    assert_eq!(word_search(&grid(&["CC", "xC"]), &["C"], SearchOptions::default()).len(), 3);
+    let wrap = SearchOptions { wrap: true, ..SearchOptions::default() };
+    assert_eq!(word_search(&grid(&["C"]), &["C"], wrap).len(), 1);
+}
+
+#[test]
+fn palindromes_are_found_from_both_ends() {
+    let found = word_search(&grid(&["ABA"]), &["ABA"], SearchOptions::default());
+    assert_eq!(found.len(), 2);
+}
+
+#[test]
+fn xmas_in_every_direction() {
+    let rows = ["S..S..S", ".A.A.A.", "..MMM..", "SAMXMAS", "..MMM..", ".A.A.A.", "S..S..S"];
+    assert_eq!(word_search(&grid(&rows), &["XMAS"], SearchOptions::default()).len(), 8);
+}
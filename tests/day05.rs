@@ -0,0 +1,45 @@
+// Day 5 part two on rule sets that do not order every pair of pages, or order some in a cycle,
+// hand-made and generated.
+
+use aoc2024::day05;
+use aoc2024::day05::RuleConflicts;
+use aoc2024::days::{self, Outcome};
+use aoc2024::random::Rng;
+
+#[test]
+fn pages_without_a_rule_keep_their_relative_order() {
+    // Only 3|1 relates pages of the update, so it is fixed as 5,3,7,2,1
+    let input = "3|1\n9|8\n\n1,5,3,7,2\n";
+    assert_eq!(day05::part_two(input), 7);
+}
+
+#[test]
+fn a_cycle_within_an_update_is_reported() {
+    let input = "1|2\n2|3\n3|1\n\n3,2,1\n";
+    let Outcome::Failed(message) = days::run_caught(|input| day05::part_two(input).to_string(), input) else {
+        panic!("an update with cyclic rules was ordered");
+    };
+    assert!(message.contains("cycle"), "{}", message);
+}
+
+#[test]
+fn generated_back_edges_make_part_two_fail() {
+    let conflicts = RuleConflicts { missing: 0.0, back_edges: 1 };
+    for seed in 0..20 {
+        let input = day05::generate_with(&mut Rng::new(seed), 10, conflicts);
+        let outcome = days::run_caught(|input| day05::part_two(input).to_string(), &input);
+        assert!(outcome.result().is_err_and(|message| message.contains("cycle")), "seed {}: {:?}", seed, outcome);
+    }
+}
+
+#[test]
+fn generated_inputs_without_back_edges_are_solved() {
+    for missing in [0.0, 0.1, 0.5, 1.0] {
+        let conflicts = RuleConflicts { missing, back_edges: 0 };
+        for seed in 0..20 {
+            let input = day05::generate_with(&mut Rng::new(seed), 10, conflicts);
+            let outcome = days::run_caught(|input| day05::part_two(input).to_string(), &input);
+            assert!(outcome.result().is_ok(), "missing {}, seed {}: {:?}", missing, seed, outcome);
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Running parts with `run_caught`: its own panics are kept quiet, everybody else's are not.
+
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use aoc2024::days::{run_caught, Outcome};
+
+static REPORTED: AtomicUsize = AtomicUsize::new(0);
+
+#[test]
+fn only_the_panics_of_the_part_being_run_are_kept_quiet() {
+    panic::set_hook(Box::new(|_| _ = REPORTED.fetch_add(1, Ordering::SeqCst)));
+
+    assert_eq!(run_caught(|_| panic!("caught"), ""), Outcome::Failed("panicked: caught".to_string()));
+    assert_eq!(REPORTED.load(Ordering::SeqCst), 0);
+
+    // A panic on another thread while a part runs still reaches the hook
+    let spawns_a_panic = |_: &str| {
+        let joined = thread::spawn(|| panic!("elsewhere")).join();
+        format!("{}", joined.is_err())
+    };
+    assert_eq!(run_caught(spawns_a_panic, "").result(), Ok("true"));
+    assert_eq!(REPORTED.load(Ordering::SeqCst), 1);
+
+    // And so does one after the part, on the same thread
+    assert!(panic::catch_unwind(|| panic!("after")).is_err());
+    assert_eq!(REPORTED.load(Ordering::SeqCst), 2);
+}
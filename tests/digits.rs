@@ -0,0 +1,79 @@
+// Digit arithmetic at the edges: around every power of ten, at the top of each type, and
+// with more digits asked for than the type can hold.
+
+use aoc2024::digits::{concat, num_digits, pow10, split_at_digit};
+use aoc2024::random::Rng;
+
+#[test]
+fn num_digits_around_every_power_of_ten() {
+    assert_eq!(num_digits(0u64), 1);
+    for k in 1..=19 {
+        let power: u64 = pow10(k).unwrap();
+        assert_eq!(num_digits(power - 1), k, "10^{} - 1", k);
+        assert_eq!(num_digits(power), k + 1, "10^{}", k);
+    }
+    assert_eq!(num_digits(u64::MAX), 20);
+
+    for k in 1..=38 {
+        let power: u128 = pow10(k).unwrap();
+        assert_eq!(num_digits(power - 1), k, "10^{} - 1", k);
+        assert_eq!(num_digits(power), k + 1, "10^{}", k);
+    }
+    assert_eq!(num_digits(u128::MAX), 39);
+    assert_eq!(pow10::<u128>(39), None);
+}
+
+#[test]
+fn num_digits_of_signed_values_ignores_the_sign() {
+    assert_eq!(num_digits(-1i32), 1);
+    assert_eq!(num_digits(-1000i64), 4);
+    assert_eq!(num_digits(i64::MIN), 19);
+    assert_eq!(num_digits(i128::MIN), 39);
+}
+
+#[test]
+fn num_digits_matches_the_decimal_text() {
+    let mut rng = Rng::new(29);
+    for _ in 0..2000 {
+        // Spread over every magnitude rather than uniformly, which would be almost all 20 digits
+        let n = rng.next_u64() >> rng.below(64);
+        for n in [n.saturating_sub(1), n, n.saturating_add(1)] {
+            assert_eq!(num_digits(n) as usize, n.to_string().len(), "{}", n);
+        }
+    }
+}
+
+#[test]
+fn concat_up_to_the_top_of_the_type() {
+    assert_eq!(concat(12u64, 345), Some(12345));
+    assert_eq!(concat(7u64, 0), Some(70));
+    assert_eq!(concat(u64::MAX / 10, 5), Some(u64::MAX));
+    assert_eq!(concat(u64::MAX / 10, 6), None);
+    assert_eq!(concat(u64::MAX, 0), None);
+    assert_eq!(concat(1u32, 1_000_000_000), None);
+    assert_eq!(concat(u128::MAX / 10, 5), Some(u128::MAX));
+    assert_eq!(concat(u128::MAX / 10, 6), None);
+}
+
+#[test]
+fn concat_of_signed_values() {
+    assert_eq!(concat(-12i64, 34), Some(-1234));
+    assert_eq!(concat(12i64, -34), None);
+    assert_eq!(concat(i64::MIN / 10, 8), Some(i64::MIN));
+    assert_eq!(concat(i64::MIN / 10, 9), None);
+    assert_eq!(concat(i64::MAX / 10, 8), None);
+}
+
+#[test]
+fn split_at_digit_beyond_the_width_of_the_type() {
+    assert_eq!(split_at_digit(12345u64, 0), (12345, 0));
+    assert_eq!(split_at_digit(12345u64, 2), (123, 45));
+    assert_eq!(split_at_digit(12345u64, 5), (0, 12345));
+    assert_eq!(split_at_digit(u64::MAX, 19), (1, u64::MAX - 10_000_000_000_000_000_000));
+    assert_eq!(split_at_digit(u64::MAX, 20), (0, u64::MAX));
+    assert_eq!(split_at_digit(u64::MAX, 200), (0, u64::MAX));
+    assert_eq!(split_at_digit(u32::MAX, 10), (0, u32::MAX));
+    assert_eq!(split_at_digit(u128::MAX, 39), (0, u128::MAX));
+    assert_eq!(split_at_digit(-1234i32, 2), (-12, -34));
+    assert_eq!(split_at_digit(i32::MIN, 10), (0, i32::MIN));
+}
@@ -0,0 +1,65 @@
+// Sorting and counting on disk with tiny buffers, so that every record becomes its own run.
+
+use std::env;
+use std::fs;
+
+use aoc2024::external_sort::{ExternalCounter, ExternalSorter};
+use aoc2024::random::Rng;
+
+/// Files open in this process, where the system tells.
+fn open_files() -> Option<usize> {
+    fs::read_dir("/proc/self/fd").ok().map(|entries| entries.count())
+}
+
+#[test]
+fn thousands_of_runs_merge_in_bounded_passes() {
+    let mut rng = Rng::new(0x5eed);
+    let values: Vec<u64> = (0..3000).map(|_| rng.below(500)).collect();
+    let mut sorter: ExternalSorter<u64> = ExternalSorter::new(env::temp_dir(), 1);
+    for &value in &values {
+        sorter.push(value).unwrap();
+    }
+    assert_eq!(sorter.runs(), values.len() - 1);
+
+    let before = open_files();
+    let merge = sorter.finish().unwrap();
+    // At most 64 runs are open at once; other tests running alongside may hold a few more
+    if let (Some(before), Some(after)) = (before, open_files()) {
+        assert!(after < before + 200, "{} files open for the merge", after.saturating_sub(before));
+    }
+    let sorted: Vec<u64> = merge.map(Result::unwrap).collect();
+    let mut expected = values.clone();
+    expected.sort_unstable();
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn counts_from_many_runs_are_added_up() {
+    let mut rng = Rng::new(0xc0ffee);
+    let values: Vec<u64> = (0..3000).map(|_| rng.below(100)).collect();
+    let mut counter = ExternalCounter::new(env::temp_dir(), 1);
+    for &value in &values {
+        counter.add(value).unwrap();
+    }
+    assert!(counter.runs() > 64);
+
+    let counts: Vec<(u64, u64)> = counter.finish().unwrap().map(Result::unwrap).collect();
+    let mut expected = vec![0; 100];
+    for &value in &values {
+        expected[value as usize] += 1;
+    }
+    let expected: Vec<(u64, u64)> =
+        expected.into_iter().enumerate().filter(|&(_, count)| count > 0).map(|(value, count)| (value as u64, count)).collect();
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn a_buffer_that_never_fills_stays_in_memory() {
+    let mut sorter: ExternalSorter<(u64, u64)> = ExternalSorter::new(env::temp_dir(), 10);
+    for pair in [(3, 1), (1, 2), (2, 0)] {
+        sorter.push(pair).unwrap();
+    }
+    assert_eq!(sorter.runs(), 0);
+    let sorted: Vec<(u64, u64)> = sorter.finish().unwrap().map(Result::unwrap).collect();
+    assert_eq!(sorted, [(1, 2), (2, 0), (3, 1)]);
+}
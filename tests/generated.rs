@@ -0,0 +1,20 @@
+// Every day solves the inputs of its own generator, down to the smallest size.
+
+use aoc2024::days::{Outcome, DAYS};
+use aoc2024::random::Rng;
+
+#[test]
+fn generated_inputs_of_any_size_are_solved() {
+    for day in &DAYS {
+        let Some(generate) = day.generate else { continue };
+        for size in [0, 1, 5] {
+            let input = generate(&mut Rng::new(size as u64 + 1), size);
+            for (part, solve) in [(1, Some(day.part_one)), (2, day.part_two)] {
+                let Some(solve) = solve else { continue };
+                if let Outcome::Failed(message) = aoc2024::days::run_caught(solve, &input) {
+                    panic!("day {} part {} on size {}: {}", day.number, part, size, message);
+                }
+            }
+        }
+    }
+}
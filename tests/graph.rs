@@ -0,0 +1,25 @@
+// Topological sorting on small hand-made graphs.
+
+use aoc2024::graph::DiGraph;
+
+#[test]
+fn topological_sort_keeps_a_valid_order() {
+    let graph = DiGraph::from_edges([(1, 2), (2, 3), (1, 3)]);
+    assert_eq!(graph.topological_sort(&[1, 2, 3]), Ok(vec![1, 2, 3]));
+    assert_eq!(graph.topological_sort(&[3, 2, 1]), Ok(vec![1, 2, 3]));
+}
+
+#[test]
+fn topological_sort_ignores_duplicate_nodes() {
+    let graph = DiGraph::from_edges([(1, 2)]);
+    assert_eq!(graph.topological_sort(&[1, 2, 2]), Ok(vec![1, 2]));
+    assert_eq!(graph.topological_sort(&[2, 1, 2, 1]), Ok(vec![1, 2]));
+}
+
+#[test]
+fn topological_sort_reports_a_cycle() {
+    let graph = DiGraph::from_edges([(1, 2), (2, 3), (3, 1), (0, 1)]);
+    let cycle = graph.topological_sort(&[0, 1, 1, 2, 3]).unwrap_err();
+    assert_eq!(cycle.len(), 3);
+    assert!(cycle.iter().enumerate().all(|(i, &n)| graph.has_edge(n, cycle[(i + 1) % cycle.len()])));
+}
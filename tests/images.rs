@@ -0,0 +1,73 @@
+// The PNG savers on the puzzle examples: saving twice gives the same bytes, so pictures
+// can be compared by hash, and the image has one block of pixels per cell. The SVG of
+// Day 12 draws every fence segment and side.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use aoc2024::{day06, day08, day10, day12};
+
+const DAY06: &str = "....#.....\n.........#\n..........\n..#.......\n.......#..\n..........\n.#..^.....\n........#.\n#.........\n......#...\n";
+const DAY08: &str = "............\n........0...\n.....0......\n.......0....\n....0.......\n......A.....\n............\n............\n........A...\n.........A..\n............\n............\n";
+const DAY10: &str = "89010123\n78121874\n87430965\n96549874\n45678903\n32019012\n01329801\n10456732\n";
+const DAY12: &str = "RRRRIICCFF\nRRRRIICCCF\nVVRRRCCFFF\nVVRCCCJFFF\nVVVVCJJCFE\nVVIVCCJJEE\nVVIIICJJEE\nMIIIIIJJEE\nMIIISIJEEE\nMMMISSJEEE\n";
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aoc-test-{}-{}", process::id(), name))
+}
+
+/// Saves `input` twice and checks both files are equal, and that the picture is `cells`
+/// wide and high at scale 3 if given.
+fn check_saver(save: fn(&str, &Path, u32) -> io::Result<()>, input: &str, name: &str, cells: Option<(u32, u32)>) {
+    let (first, second) = (temp_path(&format!("{}-1.png", name)), temp_path(&format!("{}-2.png", name)));
+    save(input, &first, 3).unwrap();
+    save(input, &second, 3).unwrap();
+    let bytes = fs::read(&first).unwrap();
+    let same = bytes == fs::read(&second).unwrap();
+
+    let info = png::Decoder::new(io::BufReader::new(File::open(&first).unwrap())).read_info().unwrap().info().clone();
+    fs::remove_file(&first).unwrap();
+    fs::remove_file(&second).unwrap();
+    assert!(same, "{} differs between two saves", name);
+    assert!(info.width.is_multiple_of(3) && info.height.is_multiple_of(3));
+    if let Some((width, height)) = cells {
+        assert_eq!((info.width, info.height), (width * 3, height * 3));
+    }
+}
+
+#[test]
+fn day06_maze_is_saved_deterministically() {
+    check_saver(day06::save_maze_png, DAY06, "day06", Some((10, 10)));
+}
+
+#[test]
+fn day08_map_is_saved_deterministically() {
+    // The map's width comes from whitespace-separated cells, so it is not checked here
+    check_saver(day08::save_map_png, DAY08, "day08", None);
+}
+
+#[test]
+fn day10_reachability_is_saved_deterministically() {
+    check_saver(day10::save_reachability_png, DAY10, "day10", Some((8, 8)));
+}
+
+#[test]
+fn day12_regions_are_saved_deterministically() {
+    check_saver(day12::save_regions_png, DAY12, "day12", Some((10, 10)));
+}
+
+#[test]
+fn day12_svg_draws_segments_sides_and_labels() {
+    let path = temp_path("day12.svg");
+    day12::save_regions_svg("AAAA\nBBCD\nBBCC\nEEEC\n", &path).unwrap();
+    let svg = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // Perimeters 10 + 8 + 10 + 4 + 8 unit segments, and 4 + 4 + 8 + 4 + 4 sides
+    assert_eq!(svg.matches("<polyline").count(), 40 + 24);
+    assert_eq!(svg.matches(r#"stroke-width="1.5""#).count(), 24);
+    let labels: Vec<&str> = svg.lines().filter_map(|line| line.strip_suffix("</text>")?.rsplit('>').next()).collect();
+    assert_eq!(labels, ["A", "B", "C", "D", "E"]);
+}
@@ -0,0 +1,91 @@
+// Property tests for the number theory helpers, on pseudo-random inputs
+// from a fixed seed so failures are reproducible.
+
+use aoc2024::math::{crt, egcd, gcd, lcm, mod_inv, solve_2x2};
+use aoc2024::random::Rng;
+
+const CASES: usize = 2000;
+
+#[test]
+fn gcd_divides_both_and_matches_bezout() {
+    let mut rng = Rng::new(0x1234_5678);
+    for _ in 0..CASES {
+        let (a, b) = (rng.range(-1_000_000_000, 1_000_000_000), rng.range(-1_000_000_000, 1_000_000_000));
+        let g = gcd(a, b);
+        let (g2, x, y) = egcd(a, b);
+        assert_eq!(g, g2, "gcd({}, {})", a, b);
+        assert!(g >= 0);
+        if g != 0 {
+            assert_eq!(a % g, 0);
+            assert_eq!(b % g, 0);
+        }
+        assert_eq!(a as i128 * x as i128 + b as i128 * y as i128, g as i128, "egcd({}, {})", a, b);
+    }
+}
+
+#[test]
+fn lcm_is_a_common_multiple() {
+    let mut rng = Rng::new(0x9abc_def0);
+    for _ in 0..CASES {
+        let (a, b) = (rng.range(1, 1_000_000), rng.range(1, 1_000_000));
+        let l = lcm(a, b).unwrap();
+        assert_eq!(l % a, 0);
+        assert_eq!(l % b, 0);
+        assert_eq!(l * gcd(a, b), a * b);
+    }
+    assert_eq!(lcm(i64::MAX, i64::MAX - 1), None);
+}
+
+#[test]
+fn mod_inv_inverts_when_coprime() {
+    let mut rng = Rng::new(0x0f0f_0f0f);
+    for _ in 0..CASES {
+        let m = rng.range(2, 1_000_000_007);
+        let a = rng.range(-1_000_000_000_000, 1_000_000_000_000);
+        match mod_inv(a, m) {
+            Some(inv) => {
+                assert!((0..m).contains(&inv));
+                assert_eq!((a.rem_euclid(m) as i128 * inv as i128) % m as i128, 1 % m as i128);
+            }
+            None => assert_ne!(gcd(a, m), 1),
+        }
+    }
+}
+
+#[test]
+fn crt_satisfies_every_congruence() {
+    let mut rng = Rng::new(0xdead_beef);
+    for _ in 0..CASES {
+        let x = rng.range(0, 1_000_000_000_000);
+        let congruences: Vec<(i128, i128)> = (0..rng.range(1, 4))
+            .map(|_| {
+                let m = rng.range(1, 10_000) as i128;
+                (x as i128 % m, m)
+            })
+            .collect();
+        let (r, m) = crt(&congruences).unwrap();
+        assert!((0..m).contains(&r));
+        assert_eq!(r, x as i128 % m, "{:?}", congruences);
+        for &(ri, mi) in &congruences {
+            assert_eq!(r % mi, ri);
+        }
+    }
+    assert_eq!(crt(&[(1i64, 4), (2, 6)]), None);
+}
+
+#[test]
+fn solve_2x2_recovers_integral_solutions() {
+    let mut rng = Rng::new(0x5eed);
+    for _ in 0..CASES {
+        let a = [[rng.range(-100, 100), rng.range(-100, 100)], [rng.range(-100, 100), rng.range(-100, 100)]];
+        let (x, y) = (rng.range(-1_000_000, 1_000_000), rng.range(-1_000_000, 1_000_000));
+        let b = [a[0][0] * x + a[0][1] * y, a[1][0] * x + a[1][1] * y];
+        let det = a[0][0] * a[1][1] - a[0][1] * a[1][0];
+        if det == 0 {
+            assert_eq!(solve_2x2(a, b), None);
+        } else {
+            assert_eq!(solve_2x2(a, b), Some((x, y)));
+        }
+    }
+    assert_eq!(solve_2x2([[2i64, 0], [0, 2]], [1, 2]), None);
+}
@@ -0,0 +1,28 @@
+// Parsing helpers on small inputs, including the positions they report on errors.
+
+use aoc2024::parse::{self, ParseError};
+
+#[test]
+fn ints_reads_signs_for_signed_types() {
+    assert_eq!(parse::ints::<i64>("x=-3, y=4"), Ok(vec![-3, 4]));
+    assert_eq!(parse::ints::<i64>("1-3"), Ok(vec![1, 3]));
+}
+
+#[test]
+fn ints_rejects_signs_for_unsigned_types() {
+    let err = parse::ints::<u64>("12   -3").unwrap_err();
+    assert_eq!((err.line, err.column), (1, 6));
+    assert_eq!(parse::ints::<u64>("1-3"), Ok(vec![1, 3]));
+}
+
+#[test]
+fn ints_reports_overflow_where_the_number_starts() {
+    let err = parse::ints::<u8>("1\n 2 300").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 4));
+}
+
+#[test]
+fn errors_can_be_moved_below_earlier_lines() {
+    let err = ParseError { line: 1, column: 2, message: String::new() };
+    assert_eq!(err.below(5).line, 5);
+}
@@ -0,0 +1,93 @@
+// Recording a made-up simulation of ten steps: which steps become frames, and what ends
+// up on disk as PNG frames or GIF frames.
+
+use std::cell::Cell;
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::process;
+
+use aoc2024::days;
+use aoc2024::image::{Image, Rgb};
+use aoc2024::recorder::Recorder;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("aoc-test-{}-{}", process::id(), name))
+}
+
+/// Records ten steps and returns the frames written and the steps that were drawn.
+fn record_steps(mut recorder: Recorder) -> (usize, Vec<usize>) {
+    let drawn = Cell::new(Vec::new());
+    for step in 0..10 {
+        recorder
+            .record(|| {
+                let mut steps = drawn.take();
+                steps.push(step);
+                drawn.set(steps);
+                Image::filled(2, 3, Rgb::from_label(step))
+            })
+            .unwrap();
+    }
+    (recorder.finish().unwrap(), drawn.take())
+}
+
+fn frame_files(dir: &PathBuf) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn a_disabled_recorder_never_draws() {
+    assert_eq!(record_steps(Recorder::disabled()), (0, vec![]));
+}
+
+#[test]
+fn every_keeps_the_first_step_and_every_nth_after_it() {
+    let dir = temp_path("every");
+    let (frames, drawn) = record_steps(Recorder::png_frames(&dir).every(3));
+    let files = frame_files(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!((frames, drawn), (4, vec![0, 3, 6, 9]));
+    assert_eq!(files, ["frame_00000.png", "frame_00001.png", "frame_00002.png", "frame_00003.png"]);
+}
+
+#[test]
+fn max_frames_stops_drawing() {
+    let dir = temp_path("max-frames");
+    let (frames, drawn) = record_steps(Recorder::png_frames(&dir).every(2).max_frames(3));
+    let files = frame_files(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!((frames, drawn), (3, vec![0, 2, 4]));
+    assert_eq!(files.len(), 3);
+}
+
+#[test]
+fn gif_frames_are_scaled() {
+    let path = temp_path("frames.gif");
+    let (frames, _) = record_steps(Recorder::gif(&path).every(4).scale(5));
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+    let (width, height) = (decoder.width(), decoder.height());
+    let mut decoded = 0;
+    while decoder.read_next_frame().unwrap().is_some() {
+        decoded += 1;
+    }
+    fs::remove_file(&path).unwrap();
+    assert_eq!((frames, decoded), (3, 3));
+    assert_eq!((width, height), (15, 10));
+}
+
+#[test]
+fn days_record_their_simulation() {
+    let input = "....#.....\n.........#\n..........\n..#.......\n.......#..\n..........\n.#..^.....\n........#.\n#.........\n......#...\n";
+    let record = days::find(6).unwrap().record.unwrap();
+    let dir = temp_path("day06");
+    let mut recorder = Recorder::png_frames(&dir).every(10).max_frames(4);
+    record(input, &mut recorder).unwrap();
+    let frames = recorder.finish().unwrap();
+    let files = frame_files(&dir);
+    fs::remove_dir_all(&dir).unwrap();
+    // The guard takes more than 40 steps, so the limit is reached
+    assert_eq!((frames, files.len()), (4, 4));
+}
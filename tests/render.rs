@@ -0,0 +1,33 @@
+// Printing frames as text: cropping to a fixed viewport, and ANSI colours only when asked for.
+
+use aoc2024::render::{Color, Frame, RenderOptions};
+
+fn options(color: bool, height: usize, width: usize) -> RenderOptions {
+    RenderOptions { color, top: 0, left: 0, width: Some(width), height: Some(height) }
+}
+
+#[test]
+fn a_frame_that_fits_is_printed_whole() {
+    let frame = Frame::from_text("ab\ncd\n");
+    assert_eq!(frame.render(&options(false, 5, 5)), "ab\ncd\n");
+}
+
+#[test]
+fn the_viewport_is_cropped_around_the_centre_and_kept_inside() {
+    let frame = Frame::from_text("abcde\nfghij\nklmno\npqrst\n");
+    let centred = options(false, 2, 3).centered_on(2, 2);
+    assert_eq!(frame.render(&centred), "ghi\nlmn\n[rows 1..3 of 4, cols 1..4 of 5]\n");
+
+    // Centring on a corner clamps the viewport to the frame
+    let corner = options(false, 2, 3).centered_on(3, 4);
+    assert_eq!(frame.render(&corner), "mno\nrst\n[rows 2..4 of 4, cols 2..5 of 5]\n");
+}
+
+#[test]
+fn colours_are_escape_codes_reset_at_the_end_of_the_line() {
+    let mut frame = Frame::from_text("ab\n");
+    frame.highlight([(0, 1)], Color::Red);
+    frame.label([((0, 0), 'x')], Some(Color::BrightBlue));
+    assert_eq!(frame.render(&options(true, 5, 5)), "\x1b[0m\x1b[94mx\x1b[0m\x1b[41mb\x1b[0m\n");
+    assert_eq!(frame.render(&options(false, 5, 5)), "xb\n");
+}
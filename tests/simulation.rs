@@ -0,0 +1,157 @@
+// Debugging a made-up simulation of a walker crossing a row of ten cells: stepping back
+// through the checkpoints, and stopping at cell and state breakpoints.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use aoc2024::days::Snapshot;
+use aoc2024::render::Frame;
+use aoc2024::simulation::{Breakpoint, Debugger, Simulation, Stop};
+use aoc2024::viewer::{DebuggerViewer, Viewer};
+
+const WIDTH: usize = 10;
+
+/// Walks one cell to the right per step until the last cell, counting the steps taken.
+struct Walker {
+    col: usize,
+    steps_taken: Rc<Cell<usize>>,
+}
+
+impl Walker {
+    fn new() -> (Walker, Rc<Cell<usize>>) {
+        let steps_taken = Rc::new(Cell::new(0));
+        (Walker { col: 0, steps_taken: steps_taken.clone() }, steps_taken)
+    }
+}
+
+impl Simulation for Walker {
+    type State = usize;
+
+    fn state(&self) -> &usize {
+        &self.col
+    }
+
+    fn set_state(&mut self, col: usize) {
+        self.col = col;
+    }
+
+    fn step(&mut self) {
+        self.col += 1;
+        self.steps_taken.set(self.steps_taken.get() + 1);
+    }
+
+    fn is_done(&self) -> bool {
+        self.col == WIDTH - 1
+    }
+
+    fn position(&self) -> Option<(usize, usize)> {
+        Some((0, self.col))
+    }
+}
+
+#[test]
+fn step_back_replays_from_the_checkpoint_before() {
+    let (walker, steps_taken) = Walker::new();
+    let mut debugger = Debugger::new(walker).checkpoint_every(4);
+    assert_eq!(debugger.go_to(9), 9);
+
+    // Step 8 is a checkpoint, so going back to 7 restores step 4 and replays three steps
+    steps_taken.set(0);
+    assert!(debugger.step_back());
+    assert_eq!((debugger.steps(), *debugger.state()), (8, 8));
+    assert_eq!(steps_taken.get(), 0);
+    assert!(debugger.step_back());
+    assert_eq!((debugger.steps(), *debugger.state()), (7, 7));
+    assert_eq!(steps_taken.get(), 3);
+
+    while debugger.step_back() {
+        assert_eq!(*debugger.state(), debugger.steps());
+    }
+    assert_eq!(debugger.steps(), 0);
+}
+
+#[test]
+fn go_to_stops_at_the_last_state() {
+    let (walker, _) = Walker::new();
+    let mut debugger = Debugger::new(walker).checkpoint_every(4);
+    assert_eq!(debugger.go_to(100), WIDTH - 1);
+    assert!(debugger.is_done());
+    assert!(!debugger.step());
+    assert_eq!(debugger.go_to(2), 2);
+    assert_eq!(*debugger.state(), 2);
+}
+
+#[test]
+fn run_stops_at_cell_breakpoints() {
+    let (walker, _) = Walker::new();
+    let mut debugger = Debugger::new(walker);
+    assert_eq!(debugger.add_breakpoint(Breakpoint::Cell(0, 3)), 0);
+    assert_eq!(debugger.add_breakpoint(Breakpoint::Cell(0, 6)), 1);
+
+    assert_eq!(debugger.run(), Stop::Breakpoint(0));
+    assert_eq!(debugger.steps(), 3);
+    assert_eq!(debugger.run(), Stop::Breakpoint(1));
+    assert_eq!(debugger.steps(), 6);
+    assert_eq!(debugger.run(), Stop::Done);
+    assert_eq!(debugger.steps(), WIDTH - 1);
+
+    // Going back to the start hits the first breakpoint again, until it is removed
+    debugger.go_to(0);
+    debugger.remove_breakpoint(0);
+    assert_eq!(debugger.run(), Stop::Breakpoint(0));
+    assert_eq!(debugger.steps(), 6);
+}
+
+#[test]
+fn run_stops_at_state_breakpoints() {
+    let (walker, _) = Walker::new();
+    let mut debugger = Debugger::new(walker);
+    debugger.add_breakpoint(Breakpoint::Cell(0, 8));
+    debugger.add_breakpoint(Breakpoint::state(|col: &usize| col.is_multiple_of(4)));
+
+    assert_eq!(debugger.run(), Stop::Breakpoint(1));
+    assert_eq!(debugger.steps(), 4);
+    assert_eq!(debugger.run(), Stop::Breakpoint(0));
+    assert_eq!(debugger.steps(), 8);
+    debugger.clear_breakpoints();
+    assert_eq!(debugger.run(), Stop::Done);
+}
+
+#[test]
+fn run_until_checks_the_predicate_before_breakpoints() {
+    let (walker, _) = Walker::new();
+    let mut debugger = Debugger::new(walker);
+    debugger.add_breakpoint(Breakpoint::Cell(0, 5));
+    assert_eq!(debugger.run_until(|col| *col == 5), Stop::Condition);
+    assert_eq!(debugger.run_until(|col| *col == 5), Stop::Done);
+    assert_eq!(debugger.steps(), WIDTH - 1);
+}
+
+fn walker_viewer() -> DebuggerViewer<Walker> {
+    let (walker, _) = Walker::new();
+    let render = |walker: &Walker, step: usize| Snapshot {
+        frame: Frame::filled(1, WIDTH, '.'),
+        status: format!("step {}", step),
+        focus: walker.position(),
+    };
+    DebuggerViewer::new(Debugger::new(walker), Box::new(render)).with_condition("past the middle", |col| *col > WIDTH / 2)
+}
+
+#[test]
+fn viewer_toggles_breakpoints_and_runs_to_them() {
+    let mut viewer = walker_viewer();
+    assert_eq!(viewer.toggle_cell_breakpoint(0, 2), Some(true));
+    assert!(viewer.snapshot().frame.cell(0, 2).unwrap().bg.is_some());
+    assert_eq!(viewer.toggle_condition(), Some(("past the middle", true)));
+
+    assert_eq!(viewer.run().unwrap(), "breakpoint at row 0, column 2 hit at step 2");
+    assert_eq!(viewer.snapshot().status, "step 2");
+    assert_eq!(viewer.run().unwrap(), "past the middle at step 6");
+
+    assert_eq!(viewer.toggle_condition(), Some(("past the middle", false)));
+    assert_eq!(viewer.toggle_cell_breakpoint(0, 2), Some(false));
+    assert!(viewer.snapshot().frame.cell(0, 2).unwrap().bg.is_none());
+    assert_eq!(viewer.run().unwrap(), "reached the last state at step 9");
+    assert!(viewer.step_back());
+    assert_eq!(viewer.steps(), 8);
+}
@@ -0,0 +1,89 @@
+// The free span index on small hand-made disks, and against a plain list of spans.
+
+use aoc2024::random::Rng;
+use aoc2024::spans::{FreeSpans, Span};
+
+fn free_spans(spans: &[(usize, usize)]) -> FreeSpans {
+    let mut free = FreeSpans::new();
+    for &(start, len) in spans {
+        free.insert(Span::new(start, len));
+    }
+    free
+}
+
+#[test]
+fn leftmost_fitting_looks_across_lengths() {
+    let free = free_spans(&[(20, 1), (10, 3), (30, 9), (5, 2)]);
+    assert_eq!(free.leftmost_fitting(1, 100), Some(Span::new(5, 2)));
+    assert_eq!(free.leftmost_fitting(3, 100), Some(Span::new(10, 3)));
+    assert_eq!(free.leftmost_fitting(4, 100), Some(Span::new(30, 9)));
+    assert_eq!(free.leftmost_fitting(10, 100), None);
+}
+
+#[test]
+fn spans_starting_at_or_after_the_cutoff_do_not_fit() {
+    let free = free_spans(&[(10, 3), (30, 9)]);
+    assert_eq!(free.leftmost_fitting(4, 30), None);
+    assert_eq!(free.leftmost_fitting(4, 31), Some(Span::new(30, 9)));
+    assert_eq!(free.leftmost_fitting(1, 10), None);
+    assert_eq!(free.leftmost_fitting(1, 11), Some(Span::new(10, 3)));
+}
+
+#[test]
+fn empty_spans_are_never_free() {
+    let mut free = free_spans(&[(4, 0), (7, 0)]);
+    assert!(free.is_empty());
+    assert_eq!(free.leftmost_fitting(0, usize::MAX), None);
+    assert!(FreeSpans::new().is_empty());
+    assert_eq!(FreeSpans::new().allocate(1, usize::MAX), None);
+
+    // Allocating a whole span leaves nothing behind
+    free.insert(Span::new(3, 2));
+    assert_eq!(free.allocate(2, usize::MAX), Some(Span::new(3, 2)));
+    assert!(free.is_empty());
+}
+
+#[test]
+fn allocate_keeps_the_rest_of_the_span_free() {
+    let mut free = free_spans(&[(10, 5), (2, 1)]);
+    assert_eq!(free.allocate(2, 100), Some(Span::new(10, 2)));
+    assert_eq!(free.leftmost_fitting(3, 100), Some(Span::new(12, 3)));
+    assert_eq!(free.allocate(1, 100), Some(Span::new(2, 1)));
+    assert_eq!(free.allocate(1, 100), Some(Span::new(12, 1)));
+    assert_eq!(free.take_leftmost_fitting(1, 100), Some(Span::new(13, 2)));
+    assert!(free.is_empty());
+}
+
+#[test]
+fn agrees_with_a_list_of_spans() {
+    let mut rng = Rng::new(31);
+    for _ in 0..200 {
+        // Disjoint spans of random lengths, some of them longer than the lengths seen before
+        let mut free = FreeSpans::new();
+        let mut list: Vec<Span> = Vec::new();
+        let mut offset = 0;
+        for _ in 0..rng.range(0, 30) {
+            offset += rng.range(0, 5) as usize;
+            let span = Span::new(offset, rng.range(0, 20) as usize);
+            offset = span.end();
+            free.insert(span);
+            if !span.is_empty() {
+                list.push(span);
+            }
+        }
+
+        for _ in 0..40 {
+            let (len, before) = (rng.range(1, 20) as usize, rng.range(0, offset as i64 + 1) as usize);
+            let expected = list.iter().filter(|span| span.len >= len && span.start < before).min_by_key(|span| span.start).copied();
+            assert_eq!(free.leftmost_fitting(len, before), expected);
+            if let Some(taken) = expected {
+                assert_eq!(free.allocate(len, before), Some(Span::new(taken.start, len)));
+                list.retain(|span| *span != taken);
+                if taken.len > len {
+                    list.push(Span::new(taken.start + len, taken.len - len));
+                }
+            }
+        }
+        assert_eq!(free.is_empty(), list.is_empty());
+    }
+}